@@ -1,62 +1,137 @@
+use std::{thread, time};
+
 use anyhow::{anyhow, Context, Result};
-use chrono::{Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use reqwest::{
-    blocking::{Client, RequestBuilder},
-    header::CONTENT_TYPE,
-    Method,
+    blocking::{Client as HttpClient, RequestBuilder, Response},
+    header::{CONTENT_TYPE, RETRY_AFTER},
+    Method, StatusCode,
 };
 use serde::{Deserialize, Serialize};
 
-const BASE_URL: &str = "https://api.track.toggl.com/api/v9/";
+use crate::{api_error::ApiError, config::Config};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.track.toggl.com/api/v9/";
+const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF_MILLIS: u64 = 500;
+const MAX_RETRY_DELAY_SECONDS: u64 = 60;
 
 pub struct ApiClient {
-    client: Client,
-    api_token: String,
+    client: HttpClient,
+    username: String,
+    password: String,
+    base_url: String,
+    max_retries: u32,
 }
 
 impl ApiClient {
-    pub fn new(api_token: Option<&str>) -> Result<ApiClient> {
+    pub fn new(api_token: Option<&str>, config: &Config) -> Result<ApiClient> {
         if let Some(api_token) = api_token {
-            return Ok(ApiClient {
-                client: Client::new(),
-                api_token: api_token.to_string(),
-            });
+            return ApiClient::build(api_token, "api_token", config);
         }
         return Err(anyhow!("Missing API token. Use login command to set it"));
     }
 
+    pub fn with_credentials(email: &str, password: &str, config: &Config) -> Result<ApiClient> {
+        return ApiClient::build(email, password, config);
+    }
+
+    fn build(username: &str, password: &str, config: &Config) -> Result<ApiClient> {
+        let base_url = config.api_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        let base_url = if base_url.ends_with('/') {
+            base_url.to_string()
+        } else {
+            format!("{}/", base_url)
+        };
+        let timeout = config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECONDS);
+        let client = HttpClient::builder()
+            .timeout(time::Duration::from_secs(timeout))
+            .build()
+            .context("Could not create the HTTP client")?;
+        return Ok(ApiClient {
+            client,
+            username: username.to_string(),
+            password: password.to_string(),
+            base_url,
+            max_retries: config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+        });
+    }
+
     fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
         let builder = self
             .client
-            .request(method, format!("{}{}", BASE_URL, path))
-            .basic_auth(&self.api_token, Some("api_token"))
+            .request(method, format!("{}{}", self.base_url, path))
+            .basic_auth(&self.username, Some(&self.password))
             .header(CONTENT_TYPE, "application/json");
 
         return Ok(builder);
     }
 
+    fn send(&self, builder: RequestBuilder) -> Result<Response, ApiError> {
+        let request = builder.build().map_err(ApiError::InvalidRequest)?;
+        // A POST that timed out or failed on the server may still have created the entry,
+        // so it is only retried when Toggl certainly did not process it.
+        let idempotent = !matches!(*request.method(), Method::POST | Method::PATCH);
+        let mut attempt = 0;
+        loop {
+            let result = self.client.execute(
+                request
+                    .try_clone()
+                    .expect("Request body should be cloneable"),
+            );
+            let retry_delay = match &result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    Some(retry_after(response).unwrap_or_else(|| backoff(attempt)))
+                }
+                Ok(response) if response.status().is_server_error() && idempotent => {
+                    Some(backoff(attempt))
+                }
+                Err(error) if error.is_connect() || (error.is_timeout() && idempotent) => {
+                    Some(backoff(attempt))
+                }
+                _ => None,
+            };
+            if let Some(retry_delay) = retry_delay {
+                if attempt < self.max_retries {
+                    thread::sleep(retry_delay);
+                    attempt += 1;
+                    continue;
+                }
+            }
+
+            let response = result.map_err(ApiError::Network)?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            let body = response.text().unwrap_or_default();
+            return Err(ApiError::from_response(status, body));
+        }
+    }
+
     pub fn get_recent_entries(&self) -> Result<Vec<TimeEntryDto>> {
         return self
-            .request(Method::GET, "me/time_entries")?
-            .send()?
+            .send(self.request(Method::GET, "me/time_entries")?)?
             .json()
             .context("Could not get time entries");
     }
 
-    pub fn get_entries_from_day(&self, day: NaiveDate) -> Result<Vec<TimeEntryDto>> {
-        return self
+    pub fn get_entries_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<TimeEntryDto>> {
+        let request = self
             .request(Method::GET, "me/time_entries")?
-            .query(&[("start_date", format!("{}", day))])
-            .query(&[("end_date", format!("{}", day + Duration::days(1)))])
-            .send()?
+            .query(&[("meta", "true")])
+            .query(&[("start_date", format!("{}", from))])
+            .query(&[("end_date", format!("{}", to + Duration::days(1)))]);
+        return self
+            .send(request)?
             .json()
             .context("Could not get time entries");
     }
 
     pub fn get_current_entry(&self) -> Result<Option<TimeEntryDto>> {
         return self
-            .request(Method::GET, "me/time_entries/current")?
-            .send()?
+            .send(self.request(Method::GET, "me/time_entries/current")?)?
             .json()
             .context("Could not get time entry");
     }
@@ -69,8 +144,7 @@ impl ApiClient {
                 running_time_entry.workspace_id, running_time_entry.id
             );
             let stopped_time_entry: TimeEntryDto = self
-                .request(Method::PATCH, &path)?
-                .send()?
+                .send(self.request(Method::PATCH, &path)?)?
                 .json()
                 .context("Could not stop the current time entry")?;
             return Ok(Some(stopped_time_entry));
@@ -83,101 +157,417 @@ impl ApiClient {
         return self.start_time_entry(new_time_entry);
     }
 
-    pub fn start(
+    pub fn start(&self, fields: TimeEntryFields, start: DateTime<Utc>) -> Result<TimeEntryDto> {
+        let new_time_entry = NewTimeEntry {
+            workspace_id: fields.workspace_id,
+            created_with: "toggl-cli".to_string(),
+            description: fields.description,
+            project_id: fields.project_id,
+            task_id: fields.task_id,
+            tags: fields.tags,
+            billable: fields.billable,
+            start: format!("{:?}", start),
+            stop: None,
+            duration: -start.timestamp(),
+        };
+
+        return self.start_time_entry(new_time_entry);
+    }
+
+    pub fn add(
         &self,
-        workspace_id: u64,
-        description: Option<String>,
-        project_id: Option<u64>,
-        start: Option<String>,
-        time: Option<String>,
+        fields: TimeEntryFields,
+        start: DateTime<Utc>,
+        stop: DateTime<Utc>,
     ) -> Result<TimeEntryDto> {
-        let now = Utc::now();
-        let start = if let Some(start) = start {
-            let time = NaiveTime::parse_from_str(&start, "%H:%M")?;
-            let naive_date_time = Local::now().date_naive().and_time(time);
-            let time = Local
-                .from_local_datetime(&naive_date_time)
-                .single()
-                .ok_or(anyhow!("Could not convert time"))?;
-            time.with_timezone(&Utc)
-        } else if let Some(time) = time {
-            now - Duration::minutes(time.parse::<i64>()?)
-        } else {
-            now
-        };
         let new_time_entry = NewTimeEntry {
-            workspace_id,
+            workspace_id: fields.workspace_id,
             created_with: "toggl-cli".to_string(),
-            description,
-            project_id,
+            description: fields.description,
+            project_id: fields.project_id,
+            task_id: fields.task_id,
+            tags: fields.tags,
+            billable: fields.billable,
             start: format!("{:?}", start),
-            duration: -1 * start.timestamp(),
+            stop: Some(format!("{:?}", stop)),
+            duration: (stop - start).num_seconds(),
         };
 
         return self.start_time_entry(new_time_entry);
     }
 
+    pub fn get_entry(&self, id: u64) -> Result<TimeEntryDto> {
+        return self
+            .send(self.request(Method::GET, &format!("me/time_entries/{}", id))?)?
+            .json()
+            .context("Could not get time entry");
+    }
+
+    pub fn edit(
+        &self,
+        time_entry: &TimeEntryDto,
+        changes: TimeEntryChanges,
+    ) -> Result<TimeEntryDto> {
+        let current_start: DateTime<Utc> = time_entry.start.parse()?;
+        let current_stop: Option<DateTime<Utc>> = time_entry
+            .stop
+            .as_ref()
+            .map(|stop| stop.parse())
+            .transpose()?;
+
+        let mut updated_time_entry = UpdatedTimeEntry {
+            description: changes.description,
+            project_id: changes.project_id,
+            tags: changes.tags,
+            ..Default::default()
+        };
+        if changes.start.is_some() || changes.stop.is_some() {
+            let start = changes.start.unwrap_or(current_start);
+            let duration = match changes.stop.or(current_stop) {
+                Some(stop) => {
+                    if stop < start {
+                        return Err(anyhow!("Stop time cannot be before start time"));
+                    }
+                    (stop - start).num_seconds()
+                }
+                None => -start.timestamp(),
+            };
+            updated_time_entry.start = Some(format!("{:?}", start));
+            updated_time_entry.stop = changes.stop.map(|stop| format!("{:?}", stop));
+            updated_time_entry.duration = Some(duration);
+        }
+
+        let path = format!(
+            "workspaces/{}/time_entries/{}",
+            time_entry.workspace_id, time_entry.id
+        );
+        return self
+            .send(self.request(Method::PUT, &path)?.json(&updated_time_entry))?
+            .json()
+            .context("Could not update the time entry");
+    }
+
+    pub fn recreate(&self, time_entry: &TimeEntryDto) -> Result<TimeEntryDto> {
+        let new_time_entry = NewTimeEntry::copy_of(time_entry);
+        return self.start_time_entry(new_time_entry);
+    }
+
+    pub fn delete_time_entry(&self, time_entry: &TimeEntryDto) -> Result<()> {
+        let path = format!(
+            "workspaces/{}/time_entries/{}",
+            time_entry.workspace_id, time_entry.id
+        );
+        self.send(self.request(Method::DELETE, &path)?)?;
+        return Ok(());
+    }
+
     fn start_time_entry(&self, new_time_entry: NewTimeEntry) -> Result<TimeEntryDto> {
         let path = format!("workspaces/{}/time_entries", &new_time_entry.workspace_id);
         let stared_entry: TimeEntryDto = self
-            .request(Method::POST, &path)?
-            .json(&new_time_entry)
-            .send()?
+            .send(self.request(Method::POST, &path)?.json(&new_time_entry))?
             .json()
             .context("Could not start a time entry")?;
         return Ok(stared_entry);
     }
 
     pub fn get_default_workspace_id(&self) -> Result<u64> {
+        return self.get_me().map(|user| user.default_workspace_id);
+    }
+
+    pub fn get_me(&self) -> Result<User> {
         return self
-            .request(Method::GET, "me")?
-            .send()?
-            .json::<UserData>()
-            .map(|data| data.default_workspace_id)
+            .send(self.request(Method::GET, "me")?)?
+            .json::<User>()
             .context("Could not get user data");
     }
 
+    pub fn get_workspaces(&self) -> Result<Vec<Workspace>> {
+        return self
+            .send(self.request(Method::GET, "me/workspaces")?)?
+            .json::<Vec<Workspace>>()
+            .context("Could not get workspaces");
+    }
+
+    pub fn get_clients(&self) -> Result<Vec<Client>> {
+        return self
+            .send(self.request(Method::GET, "me/clients")?)?
+            .json::<Option<Vec<Client>>>()
+            .map(Option::unwrap_or_default)
+            .context("Could not get clients");
+    }
+
+    pub fn get_tasks(&self) -> Result<Vec<Task>> {
+        return self
+            .send(self.request(Method::GET, "me/tasks")?)?
+            .json::<Option<Vec<Task>>>()
+            .map(Option::unwrap_or_default)
+            .context("Could not get tasks");
+    }
+
     pub fn get_projects(&self) -> Result<Vec<Project>> {
         return self
-            .request(Method::GET, "me/projects")?
-            .send()?
+            .send(self.request(Method::GET, "me/projects")?)?
             .json::<Vec<Project>>()
             .context("Could not get projects");
     }
+
+    pub fn get_project(&self, workspace_id: u64, project_id: u64) -> Result<ProjectDetails> {
+        let path = format!("workspaces/{}/projects/{}", workspace_id, project_id);
+        return self
+            .send(self.request(Method::GET, &path)?)?
+            .json::<ProjectDetails>()
+            .context("Could not get the project");
+    }
+
+    pub fn create_project(&self, fields: ProjectFields) -> Result<ProjectDetails> {
+        let path = format!("workspaces/{}/projects", fields.workspace_id);
+        let new_project = NewProject {
+            name: fields.name,
+            active: true,
+            client_id: fields.client_id,
+            color: fields.color,
+            billable: fields.billable,
+            estimated_hours: fields.estimated_hours,
+        };
+        return self
+            .send(self.request(Method::POST, &path)?.json(&new_project))?
+            .json::<ProjectDetails>()
+            .context("Could not create the project");
+    }
+
+    pub fn update_project(
+        &self,
+        workspace_id: u64,
+        project_id: u64,
+        name: Option<&str>,
+        color: Option<&str>,
+        active: Option<bool>,
+    ) -> Result<ProjectDetails> {
+        let path = format!("workspaces/{}/projects/{}", workspace_id, project_id);
+        let updated_project = UpdatedProject {
+            name,
+            color,
+            active,
+        };
+        return self
+            .send(self.request(Method::PUT, &path)?.json(&updated_project))?
+            .json::<ProjectDetails>()
+            .context("Could not update the project");
+    }
+
+    pub fn get_tags(&self, workspace_id: u64) -> Result<Vec<Tag>> {
+        return self
+            .send(self.request(Method::GET, &format!("workspaces/{}/tags", workspace_id))?)?
+            .json::<Vec<Tag>>()
+            .context("Could not get tags");
+    }
+
+    pub fn create_tag(&self, workspace_id: u64, name: &str) -> Result<Tag> {
+        let path = format!("workspaces/{}/tags", workspace_id);
+        return self
+            .send(self.request(Method::POST, &path)?.json(&TagName { name }))?
+            .json::<Tag>()
+            .context("Could not create the tag");
+    }
+
+    pub fn rename_tag(&self, workspace_id: u64, tag_id: u64, name: &str) -> Result<Tag> {
+        let path = format!("workspaces/{}/tags/{}", workspace_id, tag_id);
+        return self
+            .send(self.request(Method::PUT, &path)?.json(&TagName { name }))?
+            .json::<Tag>()
+            .context("Could not rename the tag");
+    }
+}
+
+fn backoff(attempt: u32) -> time::Duration {
+    let millis = INITIAL_BACKOFF_MILLIS.saturating_mul(2u64.saturating_pow(attempt));
+    return time::Duration::from_millis(millis.min(MAX_RETRY_DELAY_SECONDS * 1000));
+}
+
+fn retry_after(response: &Response) -> Option<time::Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    let seconds = match value.trim().parse::<u64>() {
+        Ok(seconds) => seconds,
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value).ok()?;
+            (date.with_timezone(&Utc) - Utc::now()).num_seconds().max(0) as u64
+        }
+    };
+    return Some(time::Duration::from_secs(
+        seconds.min(MAX_RETRY_DELAY_SECONDS),
+    ));
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Project {
     pub id: u64,
+    #[serde(default)]
+    pub workspace_id: Option<u64>,
     pub name: String,
+    #[serde(default)]
+    pub billable: Option<bool>,
+    #[serde(default)]
+    pub client_id: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
-struct UserData {
-    default_workspace_id: u64,
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Client {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Task {
+    pub id: u64,
+    pub name: String,
+    pub project_id: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProjectDetails {
+    pub id: u64,
+    pub workspace_id: u64,
+    pub name: String,
+    pub client_id: Option<u64>,
+    pub color: Option<String>,
+    #[serde(default)]
+    pub billable: Option<bool>,
+    pub active: bool,
+    pub estimated_hours: Option<u32>,
+    pub actual_hours: Option<u64>,
+}
+
+pub struct ProjectFields {
+    pub workspace_id: u64,
+    pub name: String,
+    pub client_id: Option<u64>,
+    pub color: Option<String>,
+    pub billable: Option<bool>,
+    pub estimated_hours: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct NewProject {
+    name: String,
+    active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    billable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimated_hours: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct UpdatedProject<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Workspace {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Tag {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+struct TagName<'a> {
+    name: &'a str,
 }
 
 #[derive(Debug, Deserialize)]
+pub struct User {
+    pub fullname: String,
+    pub email: String,
+    pub default_workspace_id: u64,
+    #[serde(default)]
+    pub api_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct TimeEntryDto {
     pub id: u64,
     pub workspace_id: u64,
     pub description: Option<String>,
     pub project_id: Option<u64>,
+    #[serde(default)]
+    pub task_id: Option<u64>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub billable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
     pub start: String,
     pub stop: Option<String>,
     pub duration: i64,
 }
 
+#[derive(Clone, Deserialize, Serialize)]
+pub struct TimeEntryFields {
+    pub workspace_id: u64,
+    pub description: Option<String>,
+    pub project_id: Option<u64>,
+    #[serde(default)]
+    pub task_id: Option<u64>,
+    pub tags: Option<Vec<String>>,
+    pub billable: bool,
+}
+
+#[derive(Default)]
+pub struct TimeEntryChanges {
+    pub description: Option<String>,
+    pub project_id: Option<u64>,
+    pub tags: Option<Vec<String>>,
+    pub start: Option<DateTime<Utc>>,
+    pub stop: Option<DateTime<Utc>>,
+}
+
 #[derive(Serialize)]
 struct NewTimeEntry {
     workspace_id: u64,
     created_with: String,
     description: Option<String>,
     project_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+    billable: bool,
     start: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<String>,
     duration: i64,
 }
 
+#[derive(Serialize, Default)]
+struct UpdatedTimeEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<i64>,
+}
+
 impl NewTimeEntry {
     fn from_time_entry(time_entry: &TimeEntryDto) -> Result<NewTimeEntry> {
         let now = Utc::now();
@@ -186,8 +576,27 @@ impl NewTimeEntry {
             created_with: "toggl-cli".to_string(),
             description: time_entry.description.to_owned(),
             project_id: time_entry.project_id,
+            task_id: time_entry.task_id,
+            tags: time_entry.tags.to_owned(),
+            billable: time_entry.billable,
             start: format!("{:?}", now),
-            duration: -1 * now.timestamp(),
+            stop: None,
+            duration: -now.timestamp(),
         });
     }
+
+    fn copy_of(time_entry: &TimeEntryDto) -> NewTimeEntry {
+        return NewTimeEntry {
+            workspace_id: time_entry.workspace_id,
+            created_with: "toggl-cli".to_string(),
+            description: time_entry.description.to_owned(),
+            project_id: time_entry.project_id,
+            task_id: time_entry.task_id,
+            tags: time_entry.tags.to_owned(),
+            billable: time_entry.billable,
+            start: time_entry.start.to_owned(),
+            stop: time_entry.stop.to_owned(),
+            duration: time_entry.duration,
+        };
+    }
 }
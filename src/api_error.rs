@@ -0,0 +1,81 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+
+#[derive(Debug)]
+pub enum ApiError {
+    Unauthorized { status: StatusCode, body: String },
+    NotFound { body: String },
+    RateLimited { body: String },
+    Server { status: StatusCode, body: String },
+    Unexpected { status: StatusCode, body: String },
+    Network(reqwest::Error),
+    InvalidRequest(reqwest::Error),
+}
+
+impl ApiError {
+    pub fn from_response(status: StatusCode, body: String) -> ApiError {
+        let body = body.trim().to_string();
+        return match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                ApiError::Unauthorized { status, body }
+            }
+            StatusCode::NOT_FOUND => ApiError::NotFound { body },
+            StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited { body },
+            status if status.is_server_error() => ApiError::Server { status, body },
+            status => ApiError::Unexpected { status, body },
+        };
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        return match self {
+            ApiError::Unauthorized { .. } => 3,
+            ApiError::NotFound { .. } => 4,
+            ApiError::RateLimited { .. } => 5,
+            ApiError::Server { .. } => 6,
+            ApiError::Unexpected { .. } => 7,
+            ApiError::Network(_) => 8,
+            ApiError::InvalidRequest(_) => 1,
+        };
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (message, body) = match self {
+            ApiError::Unauthorized { status, body } => (
+                format!(
+                    "Toggl rejected the API token ({}). Use login command to set a valid one",
+                    status
+                ),
+                body,
+            ),
+            ApiError::NotFound { body } => ("The requested entity was not found".to_string(), body),
+            ApiError::RateLimited { body } => (
+                "Toggl rate limit reached. Try again in a moment".to_string(),
+                body,
+            ),
+            ApiError::Server { status, body } => (format!("Toggl server error ({})", status), body),
+            ApiError::Unexpected { status, body } => {
+                (format!("Unexpected response from Toggl ({})", status), body)
+            }
+            ApiError::Network(error) => return write!(f, "Could not reach Toggl: {}", error),
+            ApiError::InvalidRequest(error) => {
+                return write!(f, "Could not build the request to Toggl: {}", error)
+            }
+        };
+        if body.is_empty() {
+            return write!(f, "{}", message);
+        }
+        return write!(f, "{}: {}", message, body);
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        return match self {
+            ApiError::Network(error) | ApiError::InvalidRequest(error) => Some(error),
+            _ => None,
+        };
+    }
+}
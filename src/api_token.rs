@@ -1,31 +1,195 @@
-use anyhow::{Context, Result};
+use std::{env, fs, io::Write, path::PathBuf, process};
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
 use keyring::Entry;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, dirs::find_global_config_dir, profile};
+
+const API_TOKEN_ENV: &str = "TOGGL_API_TOKEN";
+const TOKEN_FILE: &str = "api_token";
+
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenBackend {
+    Keyring,
+    Env,
+    File,
+    Command,
+}
 
-pub enum TokenUpdateResult {
-    Deleted,
-    Updated,
+pub fn backend(config: &Config) -> TokenBackend {
+    if let Some(backend) = config.token_backend {
+        return backend;
+    }
+    if env::var(API_TOKEN_ENV).is_ok() {
+        return TokenBackend::Env;
+    }
+    return TokenBackend::Keyring;
 }
 
-pub fn get() -> Result<Option<String>> {
-    let entry = get_token_entry()?;
-    return Ok(entry.get_password().ok());
+pub fn describe_backend(config: &Config) -> Result<String> {
+    return Ok(match backend(config) {
+        TokenBackend::Keyring => "the system keyring".to_string(),
+        TokenBackend::Env => format!("the {} environment variable", API_TOKEN_ENV),
+        TokenBackend::File => format!("file {}", token_file(config)?.display()),
+        TokenBackend::Command => format!("command `{}`", token_command(config)?),
+    });
 }
 
-pub fn update(api_token: &str) -> Result<TokenUpdateResult> {
-    let entry = get_token_entry()?;
-    if api_token.is_empty() {
-        entry
-            .delete_credential()
-            .context("Could not delete api token")?;
-        return Ok(TokenUpdateResult::Deleted);
+pub fn get(config: &Config) -> Result<Option<String>> {
+    return match backend(config) {
+        TokenBackend::Keyring => {
+            let entry = get_token_entry(profile::active())?;
+            Ok(entry.get_password().ok())
+        }
+        TokenBackend::Env => Ok(env::var(API_TOKEN_ENV)
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())),
+        TokenBackend::File => read_token_file(&token_file(config)?),
+        TokenBackend::Command => run_token_command(token_command(config)?).map(Some),
+    };
+}
+
+pub fn update(api_token: &str, config: &Config) -> Result<()> {
+    match backend(config) {
+        TokenBackend::Keyring => get_token_entry(profile::active())?
+            .set_password(api_token)
+            .context("Could not set api token")?,
+        TokenBackend::File => write_token_file(&token_file(config)?, api_token)?,
+        TokenBackend::Env | TokenBackend::Command => return Err(read_only_error(config)?),
     }
-    entry
-        .set_password(api_token)
-        .context("Could not set api token")?;
-    return Ok(TokenUpdateResult::Updated);
+    return Ok(());
 }
 
-fn get_token_entry() -> Result<keyring::Entry> {
-    return Entry::new("dev.modzelewski.toggl-cli", "api_token")
+pub fn clear(config: &Config) -> Result<()> {
+    match backend(config) {
+        TokenBackend::Keyring => delete(profile::active())?,
+        TokenBackend::File => {
+            let path = token_file(config)?;
+            if path.try_exists().context("Could not read token file")? {
+                fs::remove_file(&path).context("Could not delete token file")?;
+            }
+        }
+        TokenBackend::Env | TokenBackend::Command => return Err(read_only_error(config)?),
+    }
+    return Ok(());
+}
+
+pub fn delete(profile: &str) -> Result<()> {
+    let entry = get_token_entry(profile)?;
+    return match entry.delete_credential() {
+        Err(keyring::Error::NoEntry) => Ok(()),
+        result => result.context("Could not delete api token"),
+    };
+}
+
+fn read_only_error(config: &Config) -> Result<anyhow::Error> {
+    return Ok(anyhow!(
+        "The API token is read from {} and cannot be changed by toggl-cli",
+        describe_backend(config)?
+    ));
+}
+
+fn get_token_entry(profile: &str) -> Result<keyring::Entry> {
+    let user = if profile == profile::DEFAULT_PROFILE {
+        "api_token".to_string()
+    } else {
+        format!("api_token:{}", profile)
+    };
+    return Entry::new("dev.modzelewski.toggl-cli", &user)
         .context("Could not create keyring entry");
 }
+
+fn token_file(config: &Config) -> Result<PathBuf> {
+    return match &config.token_file {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Ok(find_global_config_dir()?.join(TOKEN_FILE)),
+    };
+}
+
+fn token_command(config: &Config) -> Result<&str> {
+    return config
+        .token_command
+        .as_deref()
+        .context("Token command is not set. Use set --global --token-command to set it");
+}
+
+fn read_token_file(path: &PathBuf) -> Result<Option<String>> {
+    if !path.try_exists().context("Could not read token file")? {
+        return Ok(None);
+    }
+    check_permissions(path)?;
+    let token = fs::read_to_string(path).context("Could not read token file")?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Ok(None);
+    }
+    return Ok(Some(token.to_string()));
+}
+
+fn write_token_file(path: &PathBuf, api_token: &str) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).context("Could not open token file")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .context("Could not restrict token file permissions")?;
+    }
+    file.write_all(api_token.as_bytes())
+        .context("Could not write token file")?;
+    return Ok(());
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &PathBuf) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(path)
+        .context("Could not read token file permissions")?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        return Err(anyhow!(
+            "Token file {} can be read by other users. Run `chmod 600 {}`",
+            path.display(),
+            path.display()
+        ));
+    }
+    return Ok(());
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &PathBuf) -> Result<()> {
+    return Ok(());
+}
+
+fn run_token_command(command: &str) -> Result<String> {
+    let output = process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stderr(process::Stdio::inherit())
+        .output()
+        .with_context(|| format!("Could not run token command `{}`", command))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Token command `{}` failed with {}",
+            command,
+            output.status
+        ));
+    }
+    let token = String::from_utf8(output.stdout).context("Token command printed invalid UTF-8")?;
+    let token = token.lines().next().unwrap_or_default().trim().to_string();
+    if token.is_empty() {
+        return Err(anyhow!("Token command `{}` printed no token", command));
+    }
+    return Ok(token);
+}
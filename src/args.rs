@@ -1,14 +1,47 @@
+use chrono::Weekday;
 use clap::CommandFactory;
 use clap::{Parser, Subcommand, ValueHint};
 use clap_complete::generate;
 use clap_complete::Shell;
-use std::io;
+use std::{io, path::PathBuf};
+
+use crate::{
+    api_token::TokenBackend,
+    output::OutputFormat,
+    summary::{GroupBy, SortBy},
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
     #[command(subcommand)]
     pub command: Option<Command>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t,
+        help = "Output format"
+    )]
+    pub output: OutputFormat,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Skip the local cache and fetch fresh data"
+    )]
+    pub refresh: bool,
+
+    #[arg(long, global = true, help = "Workspace to use, by name or id")]
+    pub workspace: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Profile to use, defaults to TOGGL_PROFILE"
+    )]
+    pub profile: Option<String>,
 }
 
 impl Args {
@@ -31,38 +64,192 @@ pub enum Command {
     Start {
         #[arg(value_hint = ValueHint::Other)]
         description: Option<String>,
-        #[arg(long, short, help = "Project id")]
-        project_id: Option<u64>,
-        #[arg(long, short, help = "Start time")]
+        #[arg(
+            long,
+            short,
+            alias = "project-id",
+            help = "Project name, unique prefix or id"
+        )]
+        project: Option<String>,
+        #[arg(long, help = "Task name, unique prefix or id")]
+        task: Option<String>,
+        #[arg(
+            long,
+            short,
+            allow_hyphen_values = true,
+            help = "Start time, e.g. 9:15, 9am, yesterday 17:00 or -20m"
+        )]
         start: Option<String>,
-        #[arg(long, short, help = "Running time")]
+        #[arg(long, short, help = "Running time, e.g. 20m or 1h30m")]
         time: Option<String>,
+        #[arg(long = "tag", help = "Tag, can be used multiple times")]
+        tags: Vec<String>,
+        #[arg(long, overrides_with = "non_billable", help = "Mark as billable")]
+        billable: bool,
+        #[arg(long, overrides_with = "billable", help = "Mark as non-billable")]
+        non_billable: bool,
+    },
+
+    #[command(
+        about = "Add a completed time entry",
+        after_help = "Examples:\n  add \"9:00-10:30\" \"Code review\"\n  add \"yesterday 9:00-10:30\"\n  add \"2026-10-12 13:00 +45m\""
+    )]
+    Add {
+        #[arg(
+            value_hint = ValueHint::Other,
+            help = "Time range: [today|yesterday|YYYY-MM-DD] START-END or START +DURATION"
+        )]
+        time_range: String,
+        #[arg(value_hint = ValueHint::Other)]
+        description: Option<String>,
+        #[arg(
+            long,
+            short,
+            alias = "project-id",
+            help = "Project name, unique prefix or id"
+        )]
+        project: Option<String>,
+        #[arg(long, help = "Task name, unique prefix or id")]
+        task: Option<String>,
+        #[arg(long = "tag", help = "Tag, can be used multiple times")]
+        tags: Vec<String>,
+        #[arg(long, overrides_with = "non_billable", help = "Mark as billable")]
+        billable: bool,
+        #[arg(long, overrides_with = "billable", help = "Mark as non-billable")]
+        non_billable: bool,
+    },
+
+    #[command(about = "Edit the current or a given time entry")]
+    Edit {
+        #[arg(long, help = "Time entry id, defaults to the current entry")]
+        id: Option<u64>,
+        #[arg(long, short, help = "Description")]
+        description: Option<String>,
+        #[arg(
+            long,
+            short,
+            alias = "project-id",
+            help = "Project name, unique prefix or id"
+        )]
+        project: Option<String>,
+        #[arg(
+            long,
+            short,
+            allow_hyphen_values = true,
+            help = "Start time, e.g. 9:15, 9am or 1h30m ago"
+        )]
+        start: Option<String>,
+        #[arg(
+            long,
+            short = 'e',
+            allow_hyphen_values = true,
+            help = "Stop time, e.g. 10:30, 5pm or -10m"
+        )]
+        stop: Option<String>,
+        #[arg(long = "tag", help = "Replace tags, can be used multiple times")]
+        tags: Vec<String>,
     },
 
     #[command(about = "Stop the current time entry")]
     Stop,
 
+    #[command(about = "Delete the current, last or a given time entry")]
+    Delete {
+        #[arg(long, conflicts_with = "last", help = "Time entry id")]
+        id: Option<u64>,
+        #[arg(long, help = "Delete the last time entry instead of the current one")]
+        last: bool,
+        #[arg(long, short, help = "Do not ask for confirmation")]
+        yes: bool,
+    },
+
+    #[command(about = "Restore the last deleted time entry")]
+    Undo,
+
     #[command(about = "Print the current time entry")]
     Status,
 
     #[command(about = "Print recent time entries")]
-    Recent,
+    Recent {
+        #[arg(long, help = "Show time entry ids")]
+        ids: bool,
+    },
 
-    #[command(about = "Print time entries from a given day grouped by description")]
+    #[command(about = "Print time entries from a given day or period grouped by description")]
     Summary {
         #[arg(help = "Number of days before today")]
         days_before: Option<u8>,
+        #[arg(long, help = "Include only billable time entries")]
+        billable: bool,
+        #[arg(long, conflicts_with_all = ["days_before", "month", "from"], help = "Summarize the current week")]
+        week: bool,
+        #[arg(long, conflicts_with_all = ["days_before", "from"], help = "Summarize the current month")]
+        month: bool,
+        #[arg(
+            long,
+            conflicts_with = "days_before",
+            help = "First day, e.g. 2026-10-01, yesterday or monday"
+        )]
+        from: Option<String>,
+        #[arg(long, requires = "from", help = "Last day, defaults to today")]
+        to: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            help = "Group by one or more nested levels, e.g. project,description"
+        )]
+        group_by: Vec<GroupBy>,
+        #[arg(long, value_enum, default_value_t, help = "Order of the summary lines")]
+        sort: SortBy,
     },
 
+    #[command(about = "Export time entries from a date range as CSV")]
+    Export {
+        #[arg(long, help = "First day, e.g. 2026-10-01, yesterday or monday")]
+        from: String,
+        #[arg(long, help = "Last day, defaults to today")]
+        to: Option<String>,
+        #[arg(long, short, value_hint = ValueHint::FilePath, help = "Write to a file instead of stdout")]
+        file: Option<PathBuf>,
+    },
+
+    #[command(about = "Send time entry changes queued while Toggl was unreachable")]
+    Sync,
+
     #[command(about = "Restart the last time entry")]
     Restart,
 
     #[command(about = "Switch to the time entry before the current one")]
     Switch,
 
+    #[command(about = "List all workspaces")]
+    Workspaces,
+
+    #[command(about = "List all clients")]
+    Clients,
+
+    #[command(about = "List tasks, optionally of one project")]
+    Tasks {
+        #[arg(long, short, help = "Project name, unique prefix or id")]
+        project: Option<String>,
+    },
+
     #[command(about = "List all projects")]
     Projects,
 
+    #[command(about = "Create, archive, rename, recolour or show a project")]
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommand,
+    },
+
+    #[command(about = "List, create and rename tags")]
+    Tags {
+        #[command(subcommand)]
+        command: Option<TagsCommand>,
+    },
+
     #[command(about = "Print the default workspace id")]
     DefaultWorkspaceId,
 
@@ -71,16 +258,206 @@ pub enum Command {
         #[arg(long, help = "Set config globally")]
         global: bool,
 
-        #[arg(long, short, help = "Set default project id")]
-        project_id: Option<u64>,
+        #[arg(
+            long,
+            short,
+            alias = "project-id",
+            help = "Set default project by name, unique prefix or id"
+        )]
+        project: Option<String>,
 
         #[arg(long, short, help = "Set default workspace id")]
         workspace_id: Option<u64>,
+
+        #[arg(long, requires = "global", help = "Set Toggl API base URL")]
+        api_url: Option<String>,
+
+        #[arg(long, value_parser = parse_weekday, help = "Set the first day of the week, e.g. monday")]
+        week_start: Option<String>,
+
+        #[arg(long, help = "Set the HTTP request timeout in seconds")]
+        timeout: Option<u64>,
+
+        #[arg(long, help = "Set how many times failed requests are retried")]
+        max_retries: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Set for how many seconds API responses are cached, 0 disables it"
+        )]
+        cache_ttl: Option<u64>,
+
+        #[arg(
+            long,
+            help = "Set the description derived from the git branch when start gets none, e.g. \"{ticket}\""
+        )]
+        description_template: Option<String>,
+
+        #[arg(
+            long,
+            value_enum,
+            requires = "global",
+            help = "Set where the API token is stored"
+        )]
+        token_backend: Option<TokenBackend>,
+
+        #[arg(
+            long,
+            requires = "global",
+            help = "Set the token file used by the file backend"
+        )]
+        token_file: Option<String>,
+
+        #[arg(
+            long,
+            requires = "global",
+            help = "Set the command printing the token, e.g. \"pass toggl\""
+        )]
+        token_command: Option<String>,
     },
 
-    #[command(about = "Set api token")]
+    #[command(about = "Set api token, prompts for it when not given")]
     Login {
         #[arg(value_hint = ValueHint::Other)]
-        api_token: String,
+        api_token: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "api_token",
+            help = "Log in with email and password instead of the API token"
+        )]
+        email: Option<String>,
+    },
+
+    #[command(about = "Delete api token and cached projects")]
+    Logout,
+
+    #[command(about = "List, add, remove or switch profiles")]
+    Profile {
+        #[command(subcommand)]
+        command: Option<ProfileCommand>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    #[command(about = "List all profiles")]
+    List,
+
+    #[command(about = "Add a new profile")]
+    Add {
+        #[arg(value_hint = ValueHint::Other)]
+        name: String,
+    },
+
+    #[command(about = "Remove a profile with its token and configuration")]
+    Remove {
+        #[arg(value_hint = ValueHint::Other)]
+        name: String,
+    },
+
+    #[command(about = "Use a profile by default")]
+    Use {
+        #[arg(value_hint = ValueHint::Other)]
+        name: String,
+    },
+}
+
+fn parse_weekday(value: &str) -> Result<String, String> {
+    return value
+        .parse::<Weekday>()
+        .map(|_| value.to_lowercase())
+        .map_err(|_| format!("invalid weekday \"{}\", use e.g. monday", value));
+}
+
+fn parse_color(value: &str) -> Result<String, String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("invalid colour \"{}\", use e.g. #06aaf5", value));
+    }
+    return Ok(format!("#{}", hex.to_lowercase()));
+}
+
+pub fn billable_flag(billable: bool, non_billable: bool) -> Option<bool> {
+    if billable {
+        return Some(true);
+    }
+    if non_billable {
+        return Some(false);
+    }
+    return None;
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProjectCommand {
+    #[command(about = "Create a new project in the current workspace")]
+    Create {
+        #[arg(value_hint = ValueHint::Other)]
+        name: String,
+        #[arg(long, help = "Id of the project's client")]
+        client_id: Option<u64>,
+        #[arg(long, value_parser = parse_color, help = "Colour as a hex code, e.g. #06aaf5")]
+        color: Option<String>,
+        #[arg(
+            long,
+            overrides_with = "non_billable",
+            help = "Make the project billable"
+        )]
+        billable: bool,
+        #[arg(
+            long,
+            overrides_with = "billable",
+            help = "Make the project non-billable"
+        )]
+        non_billable: bool,
+        #[arg(long, help = "Estimated hours")]
+        estimate: Option<u32>,
+    },
+
+    #[command(about = "Archive a project")]
+    Archive {
+        #[arg(help = "Project name, unique prefix or id")]
+        project: String,
+    },
+
+    #[command(about = "Rename a project")]
+    Rename {
+        #[arg(help = "Project name, unique prefix or id")]
+        project: String,
+        #[arg(value_hint = ValueHint::Other)]
+        new_name: String,
+    },
+
+    #[command(about = "Change the colour of a project")]
+    Color {
+        #[arg(help = "Project name, unique prefix or id")]
+        project: String,
+        #[arg(value_parser = parse_color, help = "Colour as a hex code, e.g. #06aaf5")]
+        color: String,
+    },
+
+    #[command(about = "Show project details")]
+    Show {
+        #[arg(help = "Project name, unique prefix or id")]
+        project: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagsCommand {
+    #[command(about = "List all tags")]
+    List,
+
+    #[command(about = "Create a new tag")]
+    Create {
+        #[arg(value_hint = ValueHint::Other)]
+        name: String,
+    },
+
+    #[command(about = "Rename a tag")]
+    Rename {
+        #[arg(value_hint = ValueHint::Other)]
+        name: String,
+        #[arg(value_hint = ValueHint::Other)]
+        new_name: String,
     },
 }
@@ -0,0 +1,70 @@
+use anyhow::{Context, Ok, Result};
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use crate::dirs::find_cache_dir;
+
+#[derive(Default, Deserialize, Serialize)]
+struct Cache {
+    values: HashMap<String, CachedValue>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct CachedValue {
+    cached_at: i64,
+    value: Value,
+}
+
+pub fn get<T: DeserializeOwned>(key: &str, ttl_seconds: u64) -> Result<Option<T>> {
+    if ttl_seconds == 0 {
+        return Ok(None);
+    }
+    let cache = load()?;
+    let Some(cached) = cache.values.get(key) else {
+        return Ok(None);
+    };
+    if Utc::now().timestamp() - cached.cached_at >= ttl_seconds as i64 {
+        return Ok(None);
+    }
+    return Ok(serde_json::from_value(cached.value.clone()).ok());
+}
+
+pub fn put<T: Serialize>(key: &str, value: &T) -> Result<()> {
+    let mut cache = load()?;
+    let value = serde_json::to_value(value).context("Couldn't serialize cached value")?;
+    cache.values.insert(
+        key.to_string(),
+        CachedValue {
+            cached_at: Utc::now().timestamp(),
+            value,
+        },
+    );
+    let value = serde_json::to_string(&cache).context("Couldn't serialize cache")?;
+    fs::write(cache_path()?, value).context("Could not save cache")?;
+    return Ok(());
+}
+
+pub fn clear() -> Result<()> {
+    let path = cache_path()?;
+    if path.try_exists().context("Couldn't read cache file")? {
+        fs::remove_file(&path).context("Couldn't remove cache file")?;
+    }
+    return Ok(());
+}
+
+fn load() -> Result<Cache> {
+    let path = cache_path()?;
+    let exists = path.try_exists().context("Couldn't read cache file")?;
+    if !exists {
+        return Ok(Cache::default());
+    }
+
+    let value = fs::read_to_string(&path).context("Couldn't read cache file")?;
+    return Ok(serde_json::from_str(&value).unwrap_or_default());
+}
+
+fn cache_path() -> Result<PathBuf> {
+    return Ok(find_cache_dir()?.join("cache.json"));
+}
@@ -1,16 +1,23 @@
 use anyhow::{Context, Ok, Result};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::{env, fs, path::Path};
 
 use crate::{
-    api_client::Project,
-    dirs::{find_global_config_dir, find_local_config, get_current_dir},
+    api_client::{Client, Project, Task, Workspace},
+    api_token::TokenBackend,
+    dirs::{find_base_config_dir, find_global_config_dir, find_local_config, get_current_dir},
+    profile,
 };
 
+const API_URL_ENV: &str = "TOGGL_API_URL";
+
 pub fn load_config() -> Result<Config> {
     let config = load_global_config()?;
     if config.is_none() {
-        return Ok(Config::default());
+        return Ok(Config {
+            api_url: env::var(API_URL_ENV).ok(),
+            ..Config::default()
+        });
     }
     let config = config.unwrap();
 
@@ -26,6 +33,37 @@ pub fn load_config() -> Result<Config> {
             .and_then(|lc| lc.project_id)
             .or(config.project_id),
         projects: config.projects,
+        workspaces: config.workspaces,
+        clients: config.clients,
+        tasks: config.tasks,
+        week_start: local_config
+            .clone()
+            .and_then(|lc| lc.week_start)
+            .or(config.week_start),
+        timeout: local_config
+            .clone()
+            .and_then(|lc| lc.timeout)
+            .or(config.timeout),
+        max_retries: local_config
+            .clone()
+            .and_then(|lc| lc.max_retries)
+            .or(config.max_retries),
+        cache_ttl: local_config
+            .clone()
+            .and_then(|lc| lc.cache_ttl)
+            .or(config.cache_ttl),
+        description_template: local_config
+            .clone()
+            .and_then(|lc| lc.description_template)
+            .or(config.description_template),
+        branches: local_config
+            .clone()
+            .and_then(|lc| lc.branches)
+            .or(config.branches),
+        token_backend: config.token_backend,
+        token_file: config.token_file,
+        token_command: config.token_command,
+        api_url: env::var(API_URL_ENV).ok().or(config.api_url),
     });
 }
 
@@ -35,21 +73,70 @@ pub fn update_config(global: bool, new_config: Config) -> Result<()> {
         config.update_project_id(new_config.project_id);
         config.update_workspace_id(new_config.workspace_id);
         config.update_projects(new_config.projects);
+        config.update_workspaces(new_config.workspaces);
+        config.update_clients(new_config.clients);
+        config.update_tasks(new_config.tasks);
+        config.update_api_url(new_config.api_url);
+        config.update_week_start(new_config.week_start);
+        config.update_timeout(new_config.timeout);
+        config.update_max_retries(new_config.max_retries);
+        config.update_cache_ttl(new_config.cache_ttl);
+        config.update_description_template(new_config.description_template);
+        config.update_token_backend(new_config.token_backend);
+        config.update_token_file(new_config.token_file);
+        config.update_token_command(new_config.token_command);
         save_global_config(&config)?;
     } else {
         let mut config = load_current_dir_config()?.unwrap_or_default();
         config.update_project_id(new_config.project_id);
         config.update_workspace_id(new_config.workspace_id);
+        config.update_week_start(new_config.week_start);
+        config.update_timeout(new_config.timeout);
+        config.update_max_retries(new_config.max_retries);
+        config.update_cache_ttl(new_config.cache_ttl);
+        config.update_description_template(new_config.description_template);
         save_current_dir_config(&config)?;
     }
     return Ok(());
 }
 
+pub fn clear_cached_entities() -> Result<()> {
+    let config = load_global_config()?;
+    if let Some(mut config) = config {
+        config.projects = None;
+        config.workspaces = None;
+        config.clients = None;
+        config.tasks = None;
+        save_global_config(&config)?;
+    }
+    return Ok(());
+}
+
 #[derive(Default, Clone, Deserialize, Serialize, Debug)]
 pub struct Config {
     pub workspace_id: Option<u64>,
     pub project_id: Option<u64>,
     pub projects: Option<Vec<Project>>,
+    pub workspaces: Option<Vec<Workspace>>,
+    pub clients: Option<Vec<Client>>,
+    pub tasks: Option<Vec<Task>>,
+    pub api_url: Option<String>,
+    pub week_start: Option<String>,
+    pub timeout: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub cache_ttl: Option<u64>,
+    pub description_template: Option<String>,
+    pub branches: Option<Vec<BranchRule>>,
+    pub token_backend: Option<TokenBackend>,
+    pub token_file: Option<String>,
+    pub token_command: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct BranchRule {
+    pub pattern: String,
+    pub project: Option<String>,
+    pub tags: Option<Vec<String>>,
 }
 
 impl Config {
@@ -70,10 +157,93 @@ impl Config {
             self.projects = projects;
         }
     }
+
+    fn update_workspaces(&mut self, workspaces: Option<Vec<Workspace>>) {
+        if workspaces.is_some() {
+            self.workspaces = workspaces;
+        }
+    }
+
+    fn update_clients(&mut self, clients: Option<Vec<Client>>) {
+        if clients.is_some() {
+            self.clients = clients;
+        }
+    }
+
+    fn update_tasks(&mut self, tasks: Option<Vec<Task>>) {
+        if tasks.is_some() {
+            self.tasks = tasks;
+        }
+    }
+
+    fn update_api_url(&mut self, api_url: Option<String>) {
+        if api_url.is_some() {
+            self.api_url = api_url;
+        }
+    }
+
+    fn update_week_start(&mut self, week_start: Option<String>) {
+        if week_start.is_some() {
+            self.week_start = week_start;
+        }
+    }
+
+    fn update_timeout(&mut self, timeout: Option<u64>) {
+        if timeout.is_some() {
+            self.timeout = timeout;
+        }
+    }
+
+    fn update_max_retries(&mut self, max_retries: Option<u32>) {
+        if max_retries.is_some() {
+            self.max_retries = max_retries;
+        }
+    }
+
+    fn update_cache_ttl(&mut self, cache_ttl: Option<u64>) {
+        if cache_ttl.is_some() {
+            self.cache_ttl = cache_ttl;
+        }
+    }
+
+    fn update_description_template(&mut self, description_template: Option<String>) {
+        if description_template.is_some() {
+            self.description_template = description_template;
+        }
+    }
+
+    fn update_token_backend(&mut self, token_backend: Option<TokenBackend>) {
+        if token_backend.is_some() {
+            self.token_backend = token_backend;
+        }
+    }
+
+    fn update_token_file(&mut self, token_file: Option<String>) {
+        if token_file.is_some() {
+            self.token_file = token_file;
+        }
+    }
+
+    fn update_token_command(&mut self, token_command: Option<String>) {
+        if token_command.is_some() {
+            self.token_command = token_command;
+        }
+    }
+}
+
+pub fn load_profile_config(profile: &str) -> Result<Config> {
+    let mut config_dir = find_base_config_dir()?;
+    if let Some(subdir) = profile::subdir(profile) {
+        config_dir.push(subdir);
+    }
+    return Ok(read_global_config(&config_dir)?.unwrap_or_default());
 }
 
 fn load_global_config() -> Result<Option<Config>> {
-    let config_dir = find_global_config_dir()?;
+    return read_global_config(&find_global_config_dir()?);
+}
+
+fn read_global_config(config_dir: &Path) -> Result<Option<Config>> {
     let config_path = config_dir.join("config.toml");
     let config_exists = config_path
         .try_exists()
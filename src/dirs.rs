@@ -2,7 +2,24 @@ use anyhow::{anyhow, Context, Ok, Result};
 use directories::ProjectDirs;
 use std::{env, fs, path::PathBuf};
 
+use crate::profile;
+
 pub fn find_global_config_dir() -> Result<PathBuf> {
+    let mut config_dir = find_base_config_dir()?;
+    if let Some(subdir) = profile::subdir(profile::active()) {
+        config_dir.push(subdir);
+    }
+
+    let exists = config_dir
+        .try_exists()
+        .context("Could not access config directory")?;
+    if !exists {
+        fs::create_dir_all(&config_dir)?;
+    }
+    return Ok(config_dir);
+}
+
+pub fn find_base_config_dir() -> Result<PathBuf> {
     let dirs = ProjectDirs::from("dev", "Modzelewski", "Toggl Cli")
         .context("Could not retrieve home directory")?;
 
@@ -63,3 +80,33 @@ pub fn get_current_dir() -> Result<PathBuf> {
 
     return Ok(current);
 }
+
+pub fn find_cache_dir() -> Result<PathBuf> {
+    let mut cache_dir = find_base_cache_dir()?;
+    if let Some(subdir) = profile::subdir(profile::active()) {
+        cache_dir.push(subdir);
+    }
+
+    let exists = cache_dir
+        .try_exists()
+        .context("Could not access cache directory")?;
+    if !exists {
+        fs::create_dir_all(&cache_dir)?;
+    }
+    return Ok(cache_dir);
+}
+
+pub fn find_base_cache_dir() -> Result<PathBuf> {
+    let dirs = ProjectDirs::from("dev", "Modzelewski", "Toggl Cli")
+        .context("Could not retrieve home directory")?;
+
+    let cache_dir = dirs.cache_dir().to_owned();
+
+    let exists = cache_dir
+        .try_exists()
+        .context("Could not access cache directory")?;
+    if !exists {
+        fs::create_dir_all(&cache_dir)?;
+    }
+    return Ok(cache_dir);
+}
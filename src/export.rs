@@ -0,0 +1,63 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+
+use crate::time_entry::TimeEntry;
+
+const HEADER: [&str; 8] = [
+    "id",
+    "date",
+    "start",
+    "stop",
+    "duration",
+    "project",
+    "description",
+    "tags",
+];
+
+pub fn write_csv(writer: &mut impl Write, time_entries: &[TimeEntry]) -> Result<()> {
+    write_row(writer, &HEADER.map(String::from))?;
+    for time_entry in time_entries {
+        let duration = time_entry.elapsed();
+        write_row(
+            writer,
+            &[
+                time_entry.id.to_string(),
+                time_entry.start.format("%Y-%m-%d").to_string(),
+                time_entry.start.format("%H:%M:%S").to_string(),
+                time_entry
+                    .stop
+                    .map(|stop| stop.format("%H:%M:%S").to_string())
+                    .unwrap_or_default(),
+                format!(
+                    "{}:{:02}:{:02}",
+                    duration / 3600,
+                    duration % 3600 / 60,
+                    duration % 60
+                ),
+                time_entry.project_name.clone().unwrap_or_default(),
+                time_entry.description.clone().unwrap_or_default(),
+                time_entry.tags.join(", "),
+            ],
+        )?;
+    }
+    writer.flush().context("Could not write CSV")?;
+    return Ok(());
+}
+
+fn write_row(writer: &mut impl Write, values: &[String]) -> Result<()> {
+    let row = values
+        .iter()
+        .map(|value| escape(value))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(writer, "{}", row).context("Could not write CSV")?;
+    return Ok(());
+}
+
+fn escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    return value.to_string();
+}
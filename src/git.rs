@@ -0,0 +1,146 @@
+use std::process::{Command, Stdio};
+
+const MAIN_BRANCHES: [&str; 4] = ["main", "master", "develop", "trunk"];
+
+pub fn current_branch() -> Option<String> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let branch = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if branch.is_empty() {
+        return None;
+    }
+    return Some(branch);
+}
+
+pub fn default_description(branch: &str) -> Option<String> {
+    if MAIN_BRANCHES.contains(&branch) {
+        return None;
+    }
+    return render_template("{ticket}", branch).or_else(|| render_template("{name}", branch));
+}
+
+pub fn render_template(template: &str, branch: &str) -> Option<String> {
+    let name = branch.rsplit('/').next().unwrap_or(branch);
+    let mut description = template.replace("{branch}", branch).replace("{name}", name);
+    if description.contains("{ticket}") {
+        description = description.replace("{ticket}", &find_ticket(branch)?);
+    }
+    let description = description.trim().to_string();
+    if description.is_empty() {
+        return None;
+    }
+    return Some(description);
+}
+
+pub fn matches_pattern(pattern: &str, branch: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let branch: Vec<char> = branch.chars().collect();
+    let (mut p, mut b) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while b < branch.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, b));
+            p += 1;
+        } else if p < pattern.len() && (pattern[p] == '?' || pattern[p] == branch[b]) {
+            p += 1;
+            b += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            b = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    return pattern[p..].iter().all(|c| *c == '*');
+}
+
+fn find_ticket(branch: &str) -> Option<String> {
+    let chars: Vec<char> = branch.chars().collect();
+    for start in 0..chars.len() {
+        if !chars[start].is_ascii_uppercase()
+            || (start > 0 && chars[start - 1].is_ascii_alphanumeric())
+        {
+            continue;
+        }
+        let mut end = start;
+        while end < chars.len() && (chars[end].is_ascii_uppercase() || chars[end].is_ascii_digit())
+        {
+            end += 1;
+        }
+        if end >= chars.len() || chars[end] != '-' {
+            continue;
+        }
+        let mut digits_end = end + 1;
+        while digits_end < chars.len() && chars[digits_end].is_ascii_digit() {
+            digits_end += 1;
+        }
+        if digits_end == end + 1
+            || (digits_end < chars.len() && chars[digits_end].is_ascii_alphanumeric())
+        {
+            continue;
+        }
+        return Some(chars[start..digits_end].iter().collect());
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_templates() {
+        let branch = "feature/ABC-123-login";
+        assert_eq!(
+            render_template("{ticket}", branch),
+            Some("ABC-123".to_string())
+        );
+        assert_eq!(
+            render_template("{ticket}: {name}", branch),
+            Some("ABC-123: ABC-123-login".to_string())
+        );
+        assert_eq!(
+            render_template("{branch}", branch),
+            Some(branch.to_string())
+        );
+        assert_eq!(render_template("{ticket}", "feature/login"), None);
+        assert_eq!(render_template("", branch), None);
+    }
+
+    #[test]
+    fn derives_default_descriptions() {
+        assert_eq!(
+            default_description("fix/XY2-7_crash"),
+            Some("XY2-7".to_string())
+        );
+        assert_eq!(
+            default_description("feature/login-form"),
+            Some("login-form".to_string())
+        );
+        assert_eq!(default_description("main"), None);
+    }
+
+    #[test]
+    fn ignores_lowercase_and_embedded_keys() {
+        assert_eq!(render_template("{ticket}", "feature/abc-123"), None);
+        assert_eq!(render_template("{ticket}", "feature/xABC-123"), None);
+        assert_eq!(render_template("{ticket}", "feature/ABC-123x"), None);
+    }
+
+    #[test]
+    fn matches_branch_patterns() {
+        assert!(matches_pattern("feature/*", "feature/ABC-123-login"));
+        assert!(matches_pattern("*/ABC-*", "fix/ABC-1"));
+        assert!(matches_pattern("release-?", "release-2"));
+        assert!(matches_pattern("main", "main"));
+        assert!(!matches_pattern("feature/*", "fix/login"));
+        assert!(!matches_pattern("release-?", "release-10"));
+    }
+}
@@ -0,0 +1,59 @@
+use anyhow::{Context, Ok, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::{api_client::TimeEntryFields, dirs::find_global_config_dir};
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "operation", rename_all = "lowercase")]
+pub enum PendingOperation {
+    Start {
+        fields: TimeEntryFields,
+        start: String,
+    },
+    Stop {
+        stop: String,
+        #[serde(default)]
+        entry_start: Option<String>,
+    },
+    Add {
+        fields: TimeEntryFields,
+        start: String,
+        stop: String,
+    },
+}
+
+pub fn record(operation: PendingOperation) -> Result<()> {
+    let mut operations = load()?;
+    operations.push(operation);
+    return save(&operations);
+}
+
+pub fn load() -> Result<Vec<PendingOperation>> {
+    let path = journal_path()?;
+    let exists = path.try_exists().context("Couldn't read journal file")?;
+    if !exists {
+        return Ok(vec![]);
+    }
+
+    let value = fs::read_to_string(&path).context("Couldn't read journal file")?;
+    return serde_json::from_str(&value).context("Couldn't parse journal file");
+}
+
+pub fn save(operations: &[PendingOperation]) -> Result<()> {
+    let path = journal_path()?;
+    if operations.is_empty() {
+        if path.try_exists().context("Couldn't read journal file")? {
+            fs::remove_file(&path).context("Couldn't remove journal file")?;
+        }
+        return Ok(());
+    }
+
+    let value = serde_json::to_string(operations).context("Couldn't serialize journal")?;
+    fs::write(path, value).context("Could not save journal")?;
+    return Ok(());
+}
+
+fn journal_path() -> Result<PathBuf> {
+    return Ok(find_global_config_dir()?.join("journal.json"));
+}
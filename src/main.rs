@@ -1,25 +1,82 @@
+#![allow(clippy::needless_return)]
+
 mod api_client;
+mod api_error;
 mod api_token;
 mod args;
+mod cache;
 mod config;
 mod dirs;
+mod export;
+mod git;
+mod journal;
+mod output;
+mod profile;
+mod prompt;
+mod summary;
+mod time_entry;
+mod time_parser;
 mod toggl_client;
+mod undo;
+
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    process::ExitCode,
+};
 
-use anyhow::{Ok, Result};
-use args::{Args, Command};
+use anyhow::{anyhow, Context, Ok, Result};
+use args::{billable_flag, Args, Command, ProfileCommand, ProjectCommand, TagsCommand};
 use clap::Parser;
 
 use config::{load_config, update_config, Config};
-use toggl_client::TogglClient;
+use output::Printer;
+use summary::SummaryPeriod;
+use toggl_client::{EntryDetails, TogglClient};
+
+use crate::{api_client::ApiClient, api_error::ApiError};
 
-use crate::api_client::ApiClient;
+fn main() -> ExitCode {
+    if let Err(error) = run() {
+        eprintln!("Error: {:?}", error);
+        let exit_code = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<ApiError>())
+            .map(|api_error| api_error.exit_code())
+            .unwrap_or(1);
+        return ExitCode::from(exit_code);
+    }
+    return ExitCode::SUCCESS;
+}
 
-fn main() -> Result<()> {
+fn run() -> Result<()> {
     let args = Args::parse();
 
-    let api_token = api_token::get()?;
+    profile::select(args.profile.clone())?;
+    if !matches!(args.command, Some(Command::Profile { .. })) {
+        profile::ensure_active_exists()?;
+    }
+
     let config = load_config()?;
-    let client = TogglClient::new(api_token, config);
+    let printer = Printer::new(args.output);
+    let replay_queued_changes = replays_queued_changes(&args.command);
+    // The token is only read once a command needs the client,
+    // a token command may prompt for a passphrase.
+    let client = || -> Result<TogglClient> {
+        let api_token = api_token::get(&config)?;
+        let mut client = TogglClient::new(api_token, config.clone(), args.refresh)?;
+        if let Some(workspace) = &args.workspace {
+            client.select_workspace(workspace)?;
+        }
+        if replay_queued_changes {
+            match client.sync() {
+                Err(error) if toggl_client::is_offline(&error) => {}
+                Err(error) => eprintln!("Warning: {:#}", error),
+                Result::Ok(report) => printer.print_sync_notice(&report),
+            }
+        }
+        return Ok(client);
+    };
 
     match args.command {
         Some(command) => match command {
@@ -28,57 +85,323 @@ fn main() -> Result<()> {
             }
             Command::Start {
                 description,
-                project_id,
+                project,
+                task,
                 start,
                 time,
-            } => client?.start(description, project_id, start, time)?,
-            Command::Stop => client?.stop_current_entry()?,
-            Command::Status => client?.print_current_entry()?,
-            Command::Recent => client?.print_recent_entries()?,
-            Command::Summary { days_before } => client?.print_day_summary(days_before)?,
-            Command::Restart => client?.restart()?,
-            Command::Switch => client?.switch()?,
-            Command::Projects => client?.print_projects()?,
-            Command::DefaultWorkspaceId => client?.print_default_workspace_id()?,
+                tags,
+                billable,
+                non_billable,
+            } => {
+                let details = EntryDetails {
+                    description,
+                    project,
+                    task,
+                    tags,
+                    billable: billable_flag(billable, non_billable),
+                };
+                let started_entry = client()?.start(details, start, time)?;
+                printer.print_change_result(
+                    "Time entry started",
+                    "",
+                    started_entry.as_ref().map(Some),
+                )?
+            }
+            Command::Stop => printer.print_change_result(
+                "Stopped time entry",
+                "There are no active time entries",
+                client()?.stop_current_entry()?.as_ref().map(Option::as_ref),
+            )?,
+            Command::Delete { id, last, yes } => {
+                let result = client()?.delete(id, last, |time_entry| {
+                    Ok(yes || printer.confirm("Delete this time entry?", time_entry)?)
+                })?;
+                printer.print_delete_result(&result)?
+            }
+            Command::Undo => printer.print_entry_result(
+                "Time entry restored",
+                "There is nothing to undo",
+                client()?.undo()?.as_ref(),
+            )?,
+            Command::Status => {
+                printer.print_current_entry(client()?.get_current_entry()?.as_ref())?
+            }
+            Command::Add {
+                time_range,
+                description,
+                project,
+                task,
+                tags,
+                billable,
+                non_billable,
+            } => {
+                let details = EntryDetails {
+                    description,
+                    project,
+                    task,
+                    tags,
+                    billable: billable_flag(billable, non_billable),
+                };
+                let added_entry = client()?.add(time_range, details)?;
+                printer.print_change_result(
+                    "Time entry added",
+                    "",
+                    added_entry.as_ref().map(Some),
+                )?
+            }
+            Command::Edit {
+                id,
+                description,
+                project,
+                start,
+                stop,
+                tags,
+            } => printer.print_entry_result(
+                "Time entry updated",
+                "There are no active time entries",
+                client()?
+                    .edit(id, description, project, start, stop, tags)?
+                    .as_ref(),
+            )?,
+            Command::Recent { ids } => {
+                printer.print_recent_entries(&client()?.get_recent_entries()?, ids)?
+            }
+            Command::Summary {
+                days_before,
+                billable,
+                week,
+                month,
+                from,
+                to,
+                group_by,
+                sort,
+            } => {
+                let period = if week {
+                    SummaryPeriod::Week
+                } else if month {
+                    SummaryPeriod::Month
+                } else if let Some(from) = from {
+                    SummaryPeriod::Range { from, to }
+                } else {
+                    SummaryPeriod::Day(days_before)
+                };
+                printer.print_summary(&client()?.get_summary(period, billable, &group_by, sort)?)?
+            }
+            Command::Export { from, to, file } => {
+                let time_entries = client()?.get_entries_between(&from, to.as_deref())?;
+                match file {
+                    Some(file) => {
+                        let file = File::create(&file)
+                            .with_context(|| format!("Could not create {}", file.display()))?;
+                        export::write_csv(&mut BufWriter::new(file), &time_entries)?
+                    }
+                    None => export::write_csv(&mut io::stdout().lock(), &time_entries)?,
+                }
+            }
+            Command::Sync => printer.print_sync_result(&client()?.sync()?)?,
+            Command::Restart => printer.print_entry_result(
+                "Time entry started",
+                "There are no recent entries",
+                client()?.restart()?.as_ref(),
+            )?,
+            Command::Switch => printer.print_entry_result(
+                "Time entry started",
+                "There are no recent entries",
+                client()?.switch()?.as_ref(),
+            )?,
+            Command::Workspaces => printer.print_workspaces(&client()?.get_workspaces()?)?,
+            Command::Clients => printer.print_clients(&client()?.get_clients()?)?,
+            Command::Tasks { project } => printer.print_tasks(&client()?.get_tasks(project)?)?,
+            Command::Projects => printer.print_projects(&client()?.get_projects()?)?,
+            Command::Project { command } => match command {
+                ProjectCommand::Create {
+                    name,
+                    client_id,
+                    color,
+                    billable,
+                    non_billable,
+                    estimate,
+                } => {
+                    let project = client()?.create_project(
+                        name,
+                        client_id,
+                        color,
+                        billable_flag(billable, non_billable),
+                        estimate,
+                    )?;
+                    printer.print_project_result("Project created", &project)?
+                }
+                ProjectCommand::Archive { project } => printer.print_project_result(
+                    "Project archived",
+                    &client()?.archive_project(&project)?,
+                )?,
+                ProjectCommand::Rename { project, new_name } => printer.print_project_result(
+                    "Project renamed",
+                    &client()?.rename_project(&project, &new_name)?,
+                )?,
+                ProjectCommand::Color { project, color } => printer.print_project_result(
+                    "Project colour changed",
+                    &client()?.set_project_color(&project, &color)?,
+                )?,
+                ProjectCommand::Show { project } => {
+                    printer.print_project(&client()?.show_project(&project)?)?
+                }
+            },
+            Command::Tags { command } => match command.unwrap_or(TagsCommand::List) {
+                TagsCommand::List => printer.print_tags(&client()?.get_tags()?)?,
+                TagsCommand::Create { name } => {
+                    printer.print_tag_result("Tag created", &client()?.create_tag(&name)?)?
+                }
+                TagsCommand::Rename { name, new_name } => printer
+                    .print_tag_result("Tag renamed", &client()?.rename_tag(&name, &new_name)?)?,
+            },
+            Command::DefaultWorkspaceId => {
+                printer.print_default_workspace_id(client()?.get_default_workspace_id()?)?
+            }
             Command::Set {
                 global,
-                project_id,
+                project,
                 workspace_id,
-            } => update_config(
-                global,
-                Config {
-                    workspace_id,
-                    project_id,
-                    projects: None,
-                },
-            )?,
-            Command::Login { api_token } => login(&api_token)?,
+                api_url,
+                week_start,
+                timeout,
+                max_retries,
+                cache_ttl,
+                description_template,
+                token_backend,
+                token_file,
+                token_command,
+            } => {
+                let project_id = match project {
+                    Some(project) => match project.parse::<u64>().ok() {
+                        Some(project_id) => Some(project_id),
+                        None => Some(client()?.resolve_project(&project)?),
+                    },
+                    None => None,
+                };
+                update_config(
+                    global,
+                    Config {
+                        workspace_id,
+                        project_id,
+                        api_url,
+                        week_start,
+                        timeout,
+                        max_retries,
+                        cache_ttl,
+                        description_template,
+                        token_backend,
+                        token_file,
+                        token_command,
+                        ..Default::default()
+                    },
+                )?;
+                cache::clear()?
+            }
+            Command::Login { api_token, email } => login(api_token, email, &config)?,
+            Command::Logout => {
+                api_token::clear(&config)?;
+                config::clear_cached_entities()?;
+                cache::clear()?;
+                println!(
+                    "Deleted API token from {}",
+                    api_token::describe_backend(&config)?
+                );
+            }
+            Command::Profile { command } => match command.unwrap_or(ProfileCommand::List) {
+                ProfileCommand::List => {
+                    printer.print_profiles(&profile::list()?, profile::active())?
+                }
+                ProfileCommand::Add { name } => {
+                    profile::add(&name)?;
+                    println!(
+                        "Profile {} added. Use `toggl-cli --profile {} login` to set its token",
+                        name, name
+                    );
+                }
+                ProfileCommand::Remove { name } => {
+                    profile::remove(&name)?;
+                    println!("Profile {} removed", name);
+                }
+                ProfileCommand::Use { name } => {
+                    profile::use_profile(&name)?;
+                    println!("Using profile {} by default", name);
+                }
+            },
         },
-        None => client?.print_recent_entries()?,
+        None => printer.print_recent_entries(&client()?.get_recent_entries()?, false)?,
     }
 
     return Ok(());
 }
 
-fn login(api_token: &str) -> Result<()> {
-    let result = api_token::update(&api_token)?;
-    match result {
-        api_token::TokenUpdateResult::Deleted => println!("Deleted API token"),
-        api_token::TokenUpdateResult::Updated => {
-            println!("Updated API token");
-            let client = ApiClient::new(Some(&api_token))?;
-            let workspace_id = client.get_default_workspace_id()?;
-            let projects = client.get_projects()?;
-            println!("Setting default workspace id to {}", &workspace_id);
-            update_config(
-                true,
-                Config {
-                    workspace_id: Some(workspace_id),
-                    project_id: None,
-                    projects: Some(projects),
-                },
-            )?;
-        }
+fn replays_queued_changes(command: &Option<Command>) -> bool {
+    return !matches!(
+        command,
+        Some(
+            Command::Completions { .. }
+                | Command::Login { .. }
+                | Command::Logout
+                | Command::Profile { .. }
+                | Command::Sync
+        )
+    );
+}
+
+fn login(api_token: Option<String>, email: Option<String>, config: &Config) -> Result<()> {
+    let api_token = match (api_token, email) {
+        (Some(api_token), _) => api_token,
+        (None, Some(email)) => fetch_api_token(&email, config)?,
+        (None, None) => prompt::read_hidden("API token: ")?,
+    };
+    let api_token = api_token.trim();
+    if api_token.is_empty() {
+        return Err(anyhow!(
+            "API token cannot be empty. Use logout command to delete it"
+        ));
     }
+
+    let client = ApiClient::new(Some(api_token), config)?;
+    let user = client
+        .get_me()
+        .context("Could not validate the API token")?;
+    api_token::update(api_token, config)?;
+    cache::clear()?;
+    println!("Logged in as {} <{}>", user.fullname, user.email);
+    println!(
+        "Updated API token in {}",
+        api_token::describe_backend(config)?
+    );
+
+    let projects = client.get_projects()?;
+    let workspaces = client.get_workspaces()?;
+    let clients = client.get_clients()?;
+    let tasks = client.get_tasks()?;
+    println!(
+        "Setting default workspace id to {}",
+        &user.default_workspace_id
+    );
+    update_config(
+        true,
+        Config {
+            workspace_id: Some(user.default_workspace_id),
+            projects: Some(projects),
+            workspaces: Some(workspaces),
+            clients: Some(clients),
+            tasks: Some(tasks),
+            ..Default::default()
+        },
+    )?;
     return Ok(());
 }
+
+fn fetch_api_token(email: &str, config: &Config) -> Result<String> {
+    let password = prompt::read_hidden("Password: ")?;
+    let client = ApiClient::with_credentials(email, &password, config)?;
+    let user = client
+        .get_me()
+        .context("Could not log in with the email and password")?;
+    return user
+        .api_token
+        .context("Toggl did not return an API token for this account");
+}
@@ -0,0 +1,608 @@
+use std::io::{self, Write};
+
+use anyhow::{Context, Result};
+use chrono::{Duration, Local};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    api_client::{Client, Project, ProjectDetails, Tag, Task, Workspace},
+    summary::{GroupBy, Summary, SummaryGroup},
+    time_entry::{format_duration, TimeEntry},
+    toggl_client::{ChangeResult, DeleteResult, SyncReport},
+};
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+pub struct Printer {
+    format: OutputFormat,
+}
+
+#[derive(Serialize)]
+struct TimeEntryOutput<'a> {
+    id: u64,
+    workspace_id: u64,
+    workspace_name: Option<&'a str>,
+    description: Option<&'a str>,
+    project_id: Option<u64>,
+    project_name: Option<&'a str>,
+    client_name: Option<&'a str>,
+    task_id: Option<u64>,
+    task_name: Option<&'a str>,
+    tags: &'a [String],
+    billable: bool,
+    start: String,
+    stop: Option<String>,
+    duration: i64,
+    running: bool,
+}
+
+impl<'a> TimeEntryOutput<'a> {
+    fn from(time_entry: &'a TimeEntry) -> TimeEntryOutput<'a> {
+        return TimeEntryOutput {
+            id: time_entry.id,
+            workspace_id: time_entry.workspace_id,
+            workspace_name: time_entry.workspace_name.as_deref(),
+            description: time_entry.description.as_deref(),
+            project_id: time_entry.project_id,
+            project_name: time_entry.project_name.as_deref(),
+            client_name: time_entry.client_name.as_deref(),
+            task_id: time_entry.task_id,
+            task_name: time_entry.task_name.as_deref(),
+            tags: &time_entry.tags,
+            billable: time_entry.billable,
+            start: time_entry.start.to_rfc3339(),
+            stop: time_entry.stop.map(|stop| stop.to_rfc3339()),
+            duration: time_entry.elapsed(),
+            running: time_entry.stop.is_none(),
+        };
+    }
+}
+
+#[derive(Serialize)]
+struct SummaryOutput<'a> {
+    from: String,
+    to: String,
+    total: i64,
+    billable: i64,
+    non_billable: i64,
+    days: Vec<DayTotalOutput>,
+    projects: Vec<ProjectTotalOutput<'a>>,
+    groups: Vec<SummaryGroupOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct DayTotalOutput {
+    date: String,
+    duration: i64,
+}
+
+#[derive(Serialize)]
+struct ProjectTotalOutput<'a> {
+    project_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_name: Option<&'a str>,
+    duration: i64,
+}
+
+#[derive(Serialize)]
+struct SummaryGroupOutput<'a> {
+    group_by: GroupBy,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_name: Option<&'a str>,
+    duration: i64,
+    percentage: f64,
+    groups: Vec<SummaryGroupOutput<'a>>,
+}
+
+impl<'a> SummaryGroupOutput<'a> {
+    fn from(group: &'a SummaryGroup) -> SummaryGroupOutput<'a> {
+        return SummaryGroupOutput {
+            group_by: group.group_by,
+            name: &group.name,
+            project_name: group.project_name.as_deref(),
+            client_name: group.client_name.as_deref(),
+            duration: group.duration,
+            percentage: (group.percentage * 10.0).round() / 10.0,
+            groups: group.groups.iter().map(SummaryGroupOutput::from).collect(),
+        };
+    }
+}
+
+#[derive(Serialize)]
+struct SyncOutput<'a> {
+    synced: Vec<TimeEntryOutput<'a>>,
+    conflicts: &'a [String],
+}
+
+#[derive(Serialize)]
+struct NamedOutput<'a> {
+    id: u64,
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct ProfileOutput<'a> {
+    name: &'a str,
+    active: bool,
+}
+
+#[derive(Serialize)]
+struct WorkspaceIdOutput {
+    workspace_id: u64,
+}
+
+impl Printer {
+    pub fn new(format: OutputFormat) -> Printer {
+        return Printer { format };
+    }
+
+    pub fn print_recent_entries(&self, time_entries: &[TimeEntry], show_ids: bool) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(&entries_output(time_entries));
+        }
+
+        let show_workspace = time_entries
+            .iter()
+            .any(|entry| entry.workspace_id != time_entries[0].workspace_id);
+        let today = Local::now().date_naive();
+        let today_entries = time_entries
+            .iter()
+            .filter(|entry| entry.start.date_naive() == today)
+            .collect::<Vec<_>>();
+
+        if !today_entries.is_empty() {
+            print!(" -- Today -- ");
+            let total = today_entries
+                .iter()
+                .map(|entry| entry.elapsed())
+                .sum::<i64>();
+            print_total(total);
+        }
+
+        for time_entry in today_entries {
+            print_entry(time_entry, show_ids, show_workspace);
+        }
+
+        let older_entries = time_entries
+            .iter()
+            .filter(|entry| entry.start.date_naive() != today)
+            .take(10)
+            .collect::<Vec<_>>();
+
+        if !older_entries.is_empty() {
+            println!(" -- Older -- ");
+            for time_entry in older_entries {
+                print_entry(time_entry, show_ids, show_workspace);
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub fn print_summary(&self, summary: &Summary) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(&SummaryOutput {
+                from: summary.from.to_string(),
+                to: summary.to.to_string(),
+                total: summary.total,
+                billable: summary.billable,
+                non_billable: summary.total - summary.billable,
+                days: summary
+                    .days
+                    .iter()
+                    .map(|day| DayTotalOutput {
+                        date: day.day.to_string(),
+                        duration: day.duration,
+                    })
+                    .collect(),
+                projects: summary
+                    .projects
+                    .iter()
+                    .map(|project| ProjectTotalOutput {
+                        project_name: &project.name,
+                        client_name: project.client_name.as_deref(),
+                        duration: project.duration,
+                    })
+                    .collect(),
+                groups: summary
+                    .groups
+                    .iter()
+                    .map(SummaryGroupOutput::from)
+                    .collect(),
+            });
+        }
+
+        if summary.total > 0 {
+            if !summary.is_single_day() {
+                print!(" -- {} - {} -- ", summary.from, summary.to);
+            } else if summary.from == Local::now().date_naive() {
+                print!(" -- Today -- ");
+            } else {
+                print!(" -- {} -- ", summary.from);
+            }
+            print_total(summary.total);
+            println!(
+                "billable: {}, non-billable: {}",
+                format_duration(&Duration::seconds(summary.billable)),
+                format_duration(&Duration::seconds(summary.total - summary.billable))
+            );
+        }
+
+        if !summary.is_single_day() {
+            println!(" -- Days -- ");
+            for day in &summary.days {
+                let duration = Duration::seconds(day.duration);
+                println!(
+                    "{}\t{}",
+                    day.day.format("%a %d %b"),
+                    format_duration(&duration)
+                );
+            }
+            println!(" -- Projects -- ");
+            for project in &summary.projects {
+                let duration = Duration::seconds(project.duration);
+                println!(
+                    "{}\t[{}]",
+                    format_duration(&duration),
+                    project_label(project)
+                );
+            }
+            let group_by = summary
+                .group_by
+                .iter()
+                .map(|group_by| format!("{:?}", group_by))
+                .collect::<Vec<_>>()
+                .join(" / ");
+            println!(" -- By {} -- ", group_by.to_lowercase());
+        }
+
+        print_groups(&summary.groups, 0);
+
+        return Ok(());
+    }
+
+    pub fn print_entry_result(
+        &self,
+        message: &str,
+        empty_message: &str,
+        time_entry: Option<&TimeEntry>,
+    ) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(&time_entry.map(TimeEntryOutput::from));
+        }
+
+        match time_entry {
+            Some(time_entry) => println!("{}: {}", message, time_entry),
+            None => println!("{}", empty_message),
+        }
+        return Ok(());
+    }
+
+    pub fn print_change_result(
+        &self,
+        message: &str,
+        empty_message: &str,
+        result: ChangeResult<Option<&TimeEntry>>,
+    ) -> Result<()> {
+        let time_entry = match result {
+            ChangeResult::Applied(time_entry) => {
+                return self.print_entry_result(message, empty_message, time_entry);
+            }
+            ChangeResult::Queued(time_entry) => time_entry,
+        };
+        let queued_message = "Toggl is unreachable. The change was queued, run sync to send it";
+        if self.format == OutputFormat::Json {
+            eprintln!("{}", queued_message);
+            return print_json(&time_entry.map(TimeEntryOutput::from));
+        }
+
+        println!("{}", queued_message);
+        if let Some(time_entry) = time_entry {
+            println!("{}", time_entry);
+        }
+        return Ok(());
+    }
+
+    pub fn print_sync_result(&self, report: &SyncReport) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(&SyncOutput {
+                synced: entries_output(&report.synced),
+                conflicts: &report.conflicts,
+            });
+        }
+
+        if report.is_empty() {
+            println!("There are no queued changes");
+        }
+        for time_entry in &report.synced {
+            println!("Synced: {}", time_entry);
+        }
+        for conflict in &report.conflicts {
+            println!("Conflict: {}", conflict);
+        }
+        return Ok(());
+    }
+
+    pub fn print_sync_notice(&self, report: &SyncReport) {
+        for time_entry in &report.synced {
+            eprintln!("Synced queued change: {}", time_entry);
+        }
+        for conflict in &report.conflicts {
+            eprintln!("Conflict: {}", conflict);
+        }
+    }
+
+    pub fn print_current_entry(&self, time_entry: Option<&TimeEntry>) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(&time_entry.map(TimeEntryOutput::from));
+        }
+
+        match time_entry {
+            Some(time_entry) => println!("{}", time_entry),
+            None => println!("There are no active time entries"),
+        }
+        return Ok(());
+    }
+
+    pub fn print_delete_result(&self, result: &DeleteResult) -> Result<()> {
+        let deleted_entry = match result {
+            DeleteResult::Deleted(time_entry) => Some(time_entry.as_ref()),
+            _ => None,
+        };
+        if self.format == OutputFormat::Json {
+            return print_json(&deleted_entry.map(TimeEntryOutput::from));
+        }
+
+        match result {
+            DeleteResult::NotFound => println!("There are no time entries to delete"),
+            DeleteResult::Cancelled => println!("Time entry was not deleted"),
+            DeleteResult::Deleted(_) => println!("Time entry deleted. Use undo to restore it"),
+        }
+        return Ok(());
+    }
+
+    pub fn confirm(&self, question: &str, time_entry: &TimeEntry) -> Result<bool> {
+        let prompt = format!("{}\n{} [y/N] ", time_entry, question);
+        if self.format == OutputFormat::Json {
+            eprint!("{}", prompt);
+        } else {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+        }
+        let mut answer = String::new();
+        io::stdin()
+            .read_line(&mut answer)
+            .context("Could not read the answer")?;
+        return Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"));
+    }
+
+    pub fn print_workspaces(&self, workspaces: &[Workspace]) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            let workspaces = workspaces
+                .iter()
+                .map(|workspace| NamedOutput {
+                    id: workspace.id,
+                    name: &workspace.name,
+                })
+                .collect::<Vec<_>>();
+            return print_json(&workspaces);
+        }
+
+        workspaces
+            .iter()
+            .for_each(|workspace| println!("[{}] {}", workspace.id, workspace.name));
+        return Ok(());
+    }
+
+    pub fn print_clients(&self, clients: &[Client]) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            let clients = clients
+                .iter()
+                .map(|client| NamedOutput {
+                    id: client.id,
+                    name: &client.name,
+                })
+                .collect::<Vec<_>>();
+            return print_json(&clients);
+        }
+
+        clients
+            .iter()
+            .for_each(|client| println!("[{}] {}", client.id, client.name));
+        return Ok(());
+    }
+
+    pub fn print_tasks(&self, tasks: &[Task]) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(&tasks);
+        }
+
+        tasks
+            .iter()
+            .for_each(|task| println!("[{}] {}\tproject {}", task.id, task.name, task.project_id));
+        return Ok(());
+    }
+
+    pub fn print_projects(&self, projects: &[Project]) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            let projects = projects
+                .iter()
+                .map(|project| NamedOutput {
+                    id: project.id,
+                    name: &project.name,
+                })
+                .collect::<Vec<_>>();
+            return print_json(&projects);
+        }
+
+        projects
+            .iter()
+            .for_each(|project| println!("[{}] {}", project.id, project.name));
+        return Ok(());
+    }
+
+    pub fn print_project_result(&self, message: &str, project: &ProjectDetails) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(project);
+        }
+
+        println!("{}: [{}] {}", message, project.id, project.name);
+        return Ok(());
+    }
+
+    pub fn print_project(&self, project: &ProjectDetails) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(project);
+        }
+
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        println!("[{}] {}", project.id, project.name);
+        println!("Workspace:\t{}", project.workspace_id);
+        if let Some(client_id) = project.client_id {
+            println!("Client:\t\t{}", client_id);
+        }
+        if let Some(color) = &project.color {
+            println!("Colour:\t\t{}", color);
+        }
+        println!("Billable:\t{}", yes_no(project.billable.unwrap_or(false)));
+        println!("Active:\t\t{}", yes_no(project.active));
+        if let Some(estimated_hours) = project.estimated_hours {
+            println!("Estimate:\t{}h", estimated_hours);
+        }
+        if let Some(actual_hours) = project.actual_hours {
+            println!("Tracked:\t{}h", actual_hours);
+        }
+        return Ok(());
+    }
+
+    pub fn print_tags(&self, tags: &[Tag]) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            let tags = tags
+                .iter()
+                .map(|tag| NamedOutput {
+                    id: tag.id,
+                    name: &tag.name,
+                })
+                .collect::<Vec<_>>();
+            return print_json(&tags);
+        }
+
+        tags.iter()
+            .for_each(|tag| println!("[{}] {}", tag.id, tag.name));
+        return Ok(());
+    }
+
+    pub fn print_tag_result(&self, message: &str, tag: &Tag) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(&NamedOutput {
+                id: tag.id,
+                name: &tag.name,
+            });
+        }
+
+        println!("{}: [{}] {}", message, tag.id, tag.name);
+        return Ok(());
+    }
+
+    pub fn print_profiles(&self, profiles: &[String], active: &str) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            let profiles = profiles
+                .iter()
+                .map(|profile| ProfileOutput {
+                    name: profile,
+                    active: profile == active,
+                })
+                .collect::<Vec<_>>();
+            return print_json(&profiles);
+        }
+
+        for profile in profiles {
+            let marker = if profile == active { "*" } else { " " };
+            println!("{} {}", marker, profile);
+        }
+        return Ok(());
+    }
+
+    pub fn print_default_workspace_id(&self, workspace_id: u64) -> Result<()> {
+        if self.format == OutputFormat::Json {
+            return print_json(&WorkspaceIdOutput { workspace_id });
+        }
+
+        println!("Workspace id {}", workspace_id);
+        return Ok(());
+    }
+}
+
+fn entries_output(time_entries: &[TimeEntry]) -> Vec<TimeEntryOutput<'_>> {
+    return time_entries.iter().map(TimeEntryOutput::from).collect();
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).context("Couldn't serialize output")?;
+    println!("{}", json);
+    return Ok(());
+}
+
+fn print_groups(groups: &[SummaryGroup], depth: usize) {
+    for group in groups {
+        let duration = Duration::seconds(group.duration);
+        let label = match group.group_by {
+            GroupBy::Project => format!("[{}]", project_label(group)),
+            GroupBy::Description => match &group.project_name {
+                Some(project_name) => format!("[{}]\t{}", project_name, group.name),
+                None => group.name.to_owned(),
+            },
+            GroupBy::Tag if group.name.is_empty() => String::new(),
+            GroupBy::Tag => format!("#{}", group.name),
+            GroupBy::Client | GroupBy::Task => group.name.to_owned(),
+        };
+        println!(
+            "{}{}\t{:.0}%\t{}",
+            "  ".repeat(depth),
+            format_duration(&duration),
+            group.percentage,
+            label
+        );
+        print_groups(&group.groups, depth + 1);
+    }
+}
+
+fn project_label(group: &SummaryGroup) -> String {
+    return match &group.client_name {
+        Some(client_name) => format!("{} / {}", client_name, group.name),
+        None => group.name.to_owned(),
+    };
+}
+
+fn print_total(total: i64) {
+    let duration = Duration::seconds(total);
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() - hours * 60;
+    println!("⌛{} hours {:02} minutes", hours, minutes);
+}
+
+fn print_entry(time_entry: &TimeEntry, show_id: bool, show_workspace: bool) {
+    let line = if show_id {
+        format!("{:#}", time_entry)
+    } else {
+        format!("{}", time_entry)
+    };
+    if show_workspace {
+        let workspace = match &time_entry.workspace_name {
+            Some(workspace_name) => workspace_name.to_owned(),
+            None => time_entry.workspace_id.to_string(),
+        };
+        println!("{}\t@{}", line, workspace);
+    } else {
+        println!("{}", line);
+    }
+}
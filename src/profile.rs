@@ -0,0 +1,154 @@
+use std::{env, fs, sync::OnceLock};
+
+use anyhow::{anyhow, Context, Ok, Result};
+
+use crate::{
+    api_token::{self, TokenBackend},
+    config,
+    dirs::{find_base_cache_dir, find_base_config_dir},
+};
+
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILE_ENV: &str = "TOGGL_PROFILE";
+const PROFILES_DIR: &str = "profiles";
+const DEFAULT_PROFILE_FILE: &str = "profile";
+
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+pub fn select(profile: Option<String>) -> Result<()> {
+    let profile = match profile.or_else(|| env::var(PROFILE_ENV).ok()) {
+        Some(profile) => profile,
+        None => load_default_profile()?,
+    };
+    validate_name(&profile)?;
+    ACTIVE_PROFILE
+        .set(profile)
+        .map_err(|_| anyhow!("Profile is already selected"))?;
+    return Ok(());
+}
+
+pub fn active() -> &'static str {
+    return ACTIVE_PROFILE
+        .get()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_PROFILE);
+}
+
+pub fn ensure_active_exists() -> Result<()> {
+    let profile = active();
+    if !exists(profile)? {
+        return Err(anyhow!(
+            "Profile \"{}\" does not exist. Use profile add to create it",
+            profile
+        ));
+    }
+    return Ok(());
+}
+
+pub fn subdir(profile: &str) -> Option<String> {
+    if profile == DEFAULT_PROFILE {
+        return None;
+    }
+    return Some(format!("{}/{}", PROFILES_DIR, profile));
+}
+
+pub fn list() -> Result<Vec<String>> {
+    let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+    let profiles_dir = find_base_config_dir()?.join(PROFILES_DIR);
+    if profiles_dir
+        .try_exists()
+        .context("Could not access profiles directory")?
+    {
+        let mut named = fs::read_dir(&profiles_dir)
+            .context("Could not read profiles directory")?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect::<Vec<_>>();
+        named.sort();
+        profiles.extend(named);
+    }
+    return Ok(profiles);
+}
+
+pub fn add(name: &str) -> Result<()> {
+    validate_name(name)?;
+    if exists(name)? {
+        return Err(anyhow!("Profile \"{}\" already exists", name));
+    }
+    let subdir = subdir(name).context("The default profile cannot be added")?;
+    fs::create_dir_all(find_base_config_dir()?.join(subdir))
+        .context("Could not create profile directory")?;
+    return Ok(());
+}
+
+pub fn remove(name: &str) -> Result<()> {
+    let subdir = subdir(name).context("The default profile cannot be removed")?;
+    if !exists(name)? {
+        return Err(anyhow!("Profile \"{}\" does not exist", name));
+    }
+    if api_token::backend(&config::load_profile_config(name)?) == TokenBackend::Keyring {
+        api_token::delete(name)?;
+    }
+    fs::remove_dir_all(find_base_config_dir()?.join(&subdir))
+        .context("Could not remove profile directory")?;
+    let cache_dir = find_base_cache_dir()?.join(&subdir);
+    if cache_dir
+        .try_exists()
+        .context("Could not access cache directory")?
+    {
+        fs::remove_dir_all(cache_dir).context("Could not remove profile cache")?;
+    }
+    if load_default_profile()? == name {
+        use_profile(DEFAULT_PROFILE)?;
+    }
+    return Ok(());
+}
+
+pub fn use_profile(name: &str) -> Result<()> {
+    if !exists(name)? {
+        return Err(anyhow!("Profile \"{}\" does not exist", name));
+    }
+    let path = find_base_config_dir()?.join(DEFAULT_PROFILE_FILE);
+    if name == DEFAULT_PROFILE {
+        if path.try_exists().context("Could not read profile file")? {
+            fs::remove_file(path).context("Could not remove profile file")?;
+        }
+        return Ok(());
+    }
+    fs::write(path, name).context("Could not save profile file")?;
+    return Ok(());
+}
+
+fn load_default_profile() -> Result<String> {
+    let path = find_base_config_dir()?.join(DEFAULT_PROFILE_FILE);
+    if !path.try_exists().context("Could not read profile file")? {
+        return Ok(DEFAULT_PROFILE.to_string());
+    }
+    let profile = fs::read_to_string(path).context("Could not read profile file")?;
+    return Ok(profile.trim().to_string());
+}
+
+fn exists(name: &str) -> Result<bool> {
+    let Some(subdir) = subdir(name) else {
+        return Ok(true);
+    };
+    return find_base_config_dir()?
+        .join(subdir)
+        .try_exists()
+        .context("Could not access profile directory");
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow!(
+            "Invalid profile name \"{}\", use letters, digits, - and _",
+            name
+        ));
+    }
+    return Ok(());
+}
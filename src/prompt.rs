@@ -0,0 +1,78 @@
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    process::{self, Command, Stdio},
+};
+
+use anyhow::{anyhow, Context, Result};
+
+pub fn read_line(message: &str) -> Result<String> {
+    eprint!("{}", message);
+    io::stderr().flush().context("Could not write prompt")?;
+    let mut value = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut value)
+        .context("Could not read input")?;
+    if read == 0 {
+        return Err(anyhow!("No input given"));
+    }
+    return Ok(value.trim().to_string());
+}
+
+pub fn read_hidden(message: &str) -> Result<String> {
+    if !io::stdin().is_terminal() {
+        return read_line(message);
+    }
+    let hidden_input = HiddenInput::new()?;
+    let value = read_line(message);
+    drop(hidden_input);
+    eprintln!();
+    return value;
+}
+
+struct HiddenInput {
+    terminal_state: String,
+}
+
+impl HiddenInput {
+    fn new() -> Result<HiddenInput> {
+        let terminal_state = stty(&["-g"])?;
+        restore_on_interrupt(terminal_state.clone())?;
+        stty(&["-echo"])?;
+        return Ok(HiddenInput { terminal_state });
+    }
+}
+
+impl Drop for HiddenInput {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.terminal_state]);
+    }
+}
+
+fn restore_on_interrupt(terminal_state: String) -> Result<()> {
+    let result = ctrlc::set_handler(move || {
+        let _ = stty(&[&terminal_state]);
+        eprintln!();
+        process::exit(130);
+    });
+    return match result {
+        Err(ctrlc::Error::MultipleHandlers) => Ok(()),
+        result => result.context("Could not handle Ctrl-C at the prompt"),
+    };
+}
+
+fn stty(args: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Could not run stty to hide the input")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Could not hide the input, stty failed with {}",
+            output.status
+        ));
+    }
+    return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+}
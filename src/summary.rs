@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{time_entry::TimeEntry, time_parser};
+
+pub enum SummaryPeriod {
+    Day(Option<u8>),
+    Week,
+    Month,
+    Range { from: String, to: Option<String> },
+}
+
+pub struct Summary {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total: i64,
+    pub billable: i64,
+    pub days: Vec<DayTotal>,
+    pub projects: Vec<SummaryGroup>,
+    pub group_by: Vec<GroupBy>,
+    pub groups: Vec<SummaryGroup>,
+}
+
+pub struct DayTotal {
+    pub day: NaiveDate,
+    pub duration: i64,
+}
+
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Project,
+    Description,
+    Tag,
+    Client,
+    Task,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    Duration,
+    Name,
+    FirstStart,
+}
+
+pub struct SummaryGroup {
+    pub group_by: GroupBy,
+    pub name: String,
+    pub project_name: Option<String>,
+    pub client_name: Option<String>,
+    pub first_start: DateTime<Local>,
+    pub duration: i64,
+    pub percentage: f64,
+    pub groups: Vec<SummaryGroup>,
+}
+
+impl SummaryPeriod {
+    pub fn date_range(
+        &self,
+        today: NaiveDate,
+        week_start: Weekday,
+    ) -> Result<(NaiveDate, NaiveDate)> {
+        return match self {
+            SummaryPeriod::Day(days_before) => {
+                let day = today - Duration::days(days_before.unwrap_or(0) as i64);
+                Ok((day, day))
+            }
+            SummaryPeriod::Week => Ok((time_parser::last_weekday(today, week_start), today)),
+            SummaryPeriod::Month => Ok((today.with_day(1).unwrap(), today)),
+            SummaryPeriod::Range { from, to } => {
+                time_parser::parse_date_range_at(from, to.as_deref(), today)
+            }
+        };
+    }
+}
+
+impl Summary {
+    pub fn is_single_day(&self) -> bool {
+        return self.from == self.to;
+    }
+}
+
+pub fn summarize(
+    time_entries: &[TimeEntry],
+    from: NaiveDate,
+    to: NaiveDate,
+    group_by: &[GroupBy],
+    sort_by: SortBy,
+) -> Summary {
+    let total = time_entries
+        .iter()
+        .map(|entry| entry.elapsed())
+        .sum::<i64>();
+    let billable = time_entries
+        .iter()
+        .filter(|entry| entry.billable)
+        .map(|entry| entry.elapsed())
+        .sum::<i64>();
+
+    let mut day_totals = HashMap::new();
+    for entry in time_entries {
+        *day_totals.entry(entry.start.date_naive()).or_insert(0) += entry.elapsed();
+    }
+
+    let days = from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| DayTotal {
+            day,
+            duration: day_totals.get(&day).copied().unwrap_or(0),
+        })
+        .collect();
+
+    let entries = time_entries.iter().collect::<Vec<_>>();
+    let projects = group_entries(&entries, &[GroupBy::Project], total, false, sort_by);
+
+    let group_by = if group_by.is_empty() {
+        vec![GroupBy::Description]
+    } else {
+        group_by.to_vec()
+    };
+    let show_project = !group_by.contains(&GroupBy::Project);
+    let groups = group_entries(&entries, &group_by, total, show_project, sort_by);
+
+    return Summary {
+        from,
+        to,
+        total,
+        billable,
+        days,
+        projects,
+        group_by,
+        groups,
+    };
+}
+
+fn group_entries(
+    time_entries: &[&TimeEntry],
+    group_by: &[GroupBy],
+    total: i64,
+    show_project: bool,
+    sort_by: SortBy,
+) -> Vec<SummaryGroup> {
+    let Some((&current, nested)) = group_by.split_first() else {
+        return vec![];
+    };
+
+    let mut grouped: HashMap<(String, Option<String>), Vec<&TimeEntry>> = HashMap::new();
+    for &entry in time_entries {
+        let project_name = if current == GroupBy::Description && show_project {
+            Some(entry.project_name.clone().unwrap_or_default())
+        } else {
+            None
+        };
+        for name in group_names(entry, current) {
+            grouped
+                .entry((name, project_name.clone()))
+                .or_default()
+                .push(entry);
+        }
+    }
+
+    let mut groups = grouped
+        .into_iter()
+        .map(|((name, project_name), entries)| {
+            let duration = entries.iter().map(|entry| entry.elapsed()).sum::<i64>();
+            SummaryGroup {
+                group_by: current,
+                name,
+                project_name,
+                client_name: match current {
+                    GroupBy::Project => entries[0].client_name.clone(),
+                    _ => None,
+                },
+                first_start: entries.iter().map(|entry| entry.start).min().unwrap(),
+                duration,
+                percentage: percentage(duration, total),
+                groups: group_entries(&entries, nested, total, show_project, sort_by),
+            }
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by(|a, b| {
+        let by_name = a
+            .name
+            .cmp(&b.name)
+            .then(a.project_name.cmp(&b.project_name));
+        return match sort_by {
+            SortBy::Duration => b.duration.cmp(&a.duration).then(by_name),
+            SortBy::Name => by_name,
+            SortBy::FirstStart => a.first_start.cmp(&b.first_start).then(by_name),
+        };
+    });
+    return groups;
+}
+
+fn group_names(time_entry: &TimeEntry, group_by: GroupBy) -> Vec<String> {
+    return match group_by {
+        GroupBy::Project => vec![time_entry.project_name.clone().unwrap_or_default()],
+        GroupBy::Description => vec![time_entry.description.clone().unwrap_or_default()],
+        GroupBy::Client => vec![time_entry.client_name.clone().unwrap_or_default()],
+        GroupBy::Task => vec![time_entry.task_name.clone().unwrap_or_default()],
+        GroupBy::Tag if time_entry.tags.is_empty() => vec![String::new()],
+        GroupBy::Tag => time_entry.tags.clone(),
+    };
+}
+
+fn percentage(duration: i64, total: i64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    return duration as f64 * 100.0 / total as f64;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+    }
+
+    fn entry(
+        description: &str,
+        project: Option<&str>,
+        day: u32,
+        hour: u32,
+        minutes: i64,
+    ) -> TimeEntry {
+        let start = Local.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap();
+        return TimeEntry {
+            id: 1,
+            workspace_id: 1,
+            workspace_name: None,
+            description: Some(description.to_string()),
+            project_id: None,
+            project_name: project.map(|project| project.to_string()),
+            task_id: None,
+            task_name: None,
+            tags: vec![],
+            billable: false,
+            client_name: None,
+            start,
+            stop: Some(start + Duration::minutes(minutes)),
+            duration: minutes * 60,
+        };
+    }
+
+    #[test]
+    fn computes_period_date_ranges() {
+        let wednesday = day(4);
+        assert_eq!(
+            SummaryPeriod::Day(None)
+                .date_range(wednesday, Weekday::Mon)
+                .unwrap(),
+            (wednesday, wednesday)
+        );
+        assert_eq!(
+            SummaryPeriod::Day(Some(2))
+                .date_range(wednesday, Weekday::Mon)
+                .unwrap(),
+            (day(2), day(2))
+        );
+        assert_eq!(
+            SummaryPeriod::Week
+                .date_range(wednesday, Weekday::Mon)
+                .unwrap(),
+            (day(2), wednesday)
+        );
+        assert_eq!(
+            SummaryPeriod::Week
+                .date_range(wednesday, Weekday::Sun)
+                .unwrap(),
+            (day(1), wednesday)
+        );
+        assert_eq!(
+            SummaryPeriod::Week
+                .date_range(wednesday, Weekday::Wed)
+                .unwrap(),
+            (wednesday, wednesday)
+        );
+        assert_eq!(
+            SummaryPeriod::Month
+                .date_range(wednesday, Weekday::Mon)
+                .unwrap(),
+            (day(1), wednesday)
+        );
+        let range = SummaryPeriod::Range {
+            from: "2026-03-02".to_string(),
+            to: None,
+        };
+        assert_eq!(
+            range.date_range(wednesday, Weekday::Mon).unwrap(),
+            (day(2), wednesday)
+        );
+        let reversed = SummaryPeriod::Range {
+            from: "2026-03-04".to_string(),
+            to: Some("2026-03-02".to_string()),
+        };
+        assert!(reversed.date_range(wednesday, Weekday::Mon).is_err());
+    }
+
+    #[test]
+    fn totals_each_day_of_the_period() {
+        let mut billable = entry("Review", Some("Acme"), 2, 9, 60);
+        billable.billable = true;
+        let entries = vec![
+            billable,
+            entry("Standup", Some("Acme"), 2, 11, 15),
+            entry("Review", Some("Acme"), 4, 9, 30),
+            entry("Lunch", None, 4, 12, 45),
+        ];
+
+        let summary = summarize(&entries, day(2), day(5), &[], SortBy::Duration);
+
+        assert!(!summary.is_single_day());
+        assert_eq!(summary.total, 150 * 60);
+        assert_eq!(summary.billable, 60 * 60);
+        let days = summary
+            .days
+            .iter()
+            .map(|total| (total.day, total.duration))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            days,
+            vec![
+                (day(2), 75 * 60),
+                (day(3), 0),
+                (day(4), 75 * 60),
+                (day(5), 0)
+            ]
+        );
+        let projects = summary
+            .projects
+            .iter()
+            .map(|project| (project.name.as_str(), project.duration))
+            .collect::<Vec<_>>();
+        assert_eq!(projects, vec![("Acme", 105 * 60), ("", 45 * 60)]);
+        let descriptions = summary
+            .groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.duration))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            descriptions,
+            vec![
+                ("Review", 90 * 60),
+                ("Lunch", 45 * 60),
+                ("Standup", 15 * 60)
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_same_description_apart_per_project() {
+        let entries = vec![
+            entry("Review", Some("Acme"), 2, 9, 60),
+            entry("Review", Some("Initech"), 2, 11, 30),
+        ];
+
+        let summary = summarize(&entries, day(2), day(2), &[], SortBy::Duration);
+
+        let groups = summary
+            .groups
+            .iter()
+            .map(|group| {
+                (
+                    group.name.as_str(),
+                    group.project_name.as_deref(),
+                    group.duration,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            vec![
+                ("Review", Some("Acme"), 60 * 60),
+                ("Review", Some("Initech"), 30 * 60)
+            ]
+        );
+    }
+
+    #[test]
+    fn nests_groups_with_subtotals_and_percentages() {
+        let entries = vec![
+            entry("Review", Some("Acme"), 2, 9, 60),
+            entry("Standup", Some("Acme"), 2, 10, 30),
+            entry("Review", Some("Acme"), 2, 11, 30),
+            entry("Review", Some("Initech"), 2, 13, 80),
+        ];
+
+        let summary = summarize(
+            &entries,
+            day(2),
+            day(2),
+            &[GroupBy::Project, GroupBy::Description],
+            SortBy::Duration,
+        );
+
+        assert_eq!(summary.groups.len(), 2);
+        let acme = &summary.groups[0];
+        assert_eq!(acme.group_by, GroupBy::Project);
+        assert_eq!(acme.name, "Acme");
+        assert_eq!(acme.duration, 120 * 60);
+        assert_eq!(acme.percentage, 60.0);
+        let nested = acme
+            .groups
+            .iter()
+            .map(|group| {
+                assert_eq!(group.group_by, GroupBy::Description);
+                assert_eq!(group.project_name, None);
+                return (group.name.as_str(), group.duration, group.percentage);
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            nested,
+            vec![("Review", 90 * 60, 45.0), ("Standup", 30 * 60, 15.0)]
+        );
+        let initech = &summary.groups[1];
+        assert_eq!(initech.name, "Initech");
+        assert_eq!(initech.percentage, 40.0);
+        assert_eq!(initech.groups.len(), 1);
+    }
+
+    #[test]
+    fn groups_entries_under_each_of_their_tags() {
+        let mut both = entry("Review", None, 2, 9, 60);
+        both.tags = vec!["focus".to_string(), "code".to_string()];
+        let mut code = entry("Refactor", None, 2, 11, 30);
+        code.tags = vec!["code".to_string()];
+        let entries = vec![both, code, entry("Lunch", None, 2, 12, 30)];
+
+        let summary = summarize(&entries, day(2), day(2), &[GroupBy::Tag], SortBy::Duration);
+
+        assert_eq!(summary.total, 120 * 60);
+        let tags = summary
+            .groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.duration, group.percentage))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            vec![
+                ("code", 90 * 60, 75.0),
+                ("focus", 60 * 60, 50.0),
+                ("", 30 * 60, 25.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn sorts_groups() {
+        let entries = vec![
+            entry("Standup", None, 2, 9, 15),
+            entry("Review", None, 2, 10, 60),
+            entry("Admin", None, 2, 11, 30),
+            entry("Lunch", None, 2, 12, 30),
+        ];
+        let names = |sort_by| {
+            return summarize(&entries, day(2), day(2), &[], sort_by)
+                .groups
+                .iter()
+                .map(|group| group.name.clone())
+                .collect::<Vec<_>>();
+        };
+
+        assert_eq!(
+            names(SortBy::Duration),
+            ["Review", "Admin", "Lunch", "Standup"]
+        );
+        assert_eq!(names(SortBy::Name), ["Admin", "Lunch", "Review", "Standup"]);
+        assert_eq!(
+            names(SortBy::FirstStart),
+            ["Standup", "Review", "Admin", "Lunch"]
+        );
+    }
+
+    #[test]
+    fn includes_the_running_entry_in_the_groups() {
+        let today = Local::now().date_naive();
+        let mut running = entry("Review", None, 2, 9, 0);
+        running.start = Local::now() - Duration::minutes(30);
+        running.stop = None;
+        running.duration = -running.start.timestamp();
+
+        let summary = summarize(&[running], today, today, &[], SortBy::Duration);
+
+        assert_eq!(summary.groups.len(), 1);
+        assert_eq!(summary.groups[0].name, "Review");
+        assert!(summary.groups[0].duration >= 30 * 60);
+    }
+}
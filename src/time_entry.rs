@@ -0,0 +1,211 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, Utc};
+
+use crate::{
+    api_client::{Project, TimeEntryDto, TimeEntryFields, Workspace},
+    config::Config,
+};
+
+pub struct TimeEntry {
+    pub id: u64,
+    pub workspace_id: u64,
+    pub workspace_name: Option<String>,
+    pub description: Option<String>,
+    pub project_id: Option<u64>,
+    pub project_name: Option<String>,
+    pub task_id: Option<u64>,
+    pub task_name: Option<String>,
+    pub tags: Vec<String>,
+    pub billable: bool,
+    pub client_name: Option<String>,
+    pub start: DateTime<Local>,
+    pub stop: Option<DateTime<Local>>,
+    pub duration: i64,
+}
+
+impl TimeEntry {
+    pub fn from_dto(dto: &TimeEntryDto, config: &Config) -> Result<TimeEntry> {
+        return Ok(TimeEntry {
+            id: dto.id,
+            workspace_id: dto.workspace_id,
+            workspace_name: find_workspace_name(dto.workspace_id, &config.workspaces),
+            description: dto.description.to_owned(),
+            project_id: dto.project_id,
+            project_name: find_project(dto.project_id, config)
+                .map(|project| project.name.to_owned()),
+            task_id: dto.task_id,
+            task_name: find_task_name(dto.task_id, config),
+            tags: dto.tags.to_owned().unwrap_or_default(),
+            billable: dto.billable,
+            client_name: dto
+                .client_name
+                .to_owned()
+                .or_else(|| find_client_name(dto.project_id, config)),
+            start: dto.start.parse()?,
+            stop: dto.stop.to_owned().map(|value| value.parse()).transpose()?,
+            duration: dto.duration,
+        });
+    }
+
+    pub fn from_fields(
+        fields: &TimeEntryFields,
+        start: DateTime<Utc>,
+        stop: Option<DateTime<Utc>>,
+        config: &Config,
+    ) -> TimeEntry {
+        return TimeEntry {
+            id: 0,
+            workspace_id: fields.workspace_id,
+            workspace_name: find_workspace_name(fields.workspace_id, &config.workspaces),
+            description: fields.description.to_owned(),
+            project_id: fields.project_id,
+            project_name: find_project(fields.project_id, config)
+                .map(|project| project.name.to_owned()),
+            task_id: fields.task_id,
+            task_name: find_task_name(fields.task_id, config),
+            tags: fields.tags.to_owned().unwrap_or_default(),
+            billable: fields.billable,
+            client_name: find_client_name(fields.project_id, config),
+            start: start.with_timezone(&Local),
+            stop: stop.map(|stop| stop.with_timezone(&Local)),
+            duration: match stop {
+                Some(stop) => (stop - start).num_seconds(),
+                None => -start.timestamp(),
+            },
+        };
+    }
+
+    pub fn elapsed(&self) -> i64 {
+        if self.stop.is_some() {
+            return self.duration;
+        }
+        return Utc::now().timestamp() - self.start.timestamp();
+    }
+}
+
+fn find_project(project_id: Option<u64>, config: &Config) -> Option<&Project> {
+    let project_id = project_id?;
+    return config
+        .projects
+        .as_ref()?
+        .iter()
+        .find(|project| project.id == project_id);
+}
+
+fn find_client_name(project_id: Option<u64>, config: &Config) -> Option<String> {
+    let client_id = find_project(project_id, config)?.client_id?;
+    return config
+        .clients
+        .as_ref()?
+        .iter()
+        .find(|client| client.id == client_id)
+        .map(|client| client.name.to_owned());
+}
+
+fn find_task_name(task_id: Option<u64>, config: &Config) -> Option<String> {
+    let task_id = task_id?;
+    return config
+        .tasks
+        .as_ref()?
+        .iter()
+        .find(|task| task.id == task_id)
+        .map(|task| task.name.to_owned());
+}
+
+fn find_workspace_name(workspace_id: u64, workspaces: &Option<Vec<Workspace>>) -> Option<String> {
+    return workspaces.as_ref().and_then(|workspaces| {
+        workspaces
+            .iter()
+            .find(|workspace| workspace.id == workspace_id)
+            .map(|workspace| workspace.name.to_owned())
+    });
+}
+
+impl Display for TimeEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "[{}] ", self.id)?;
+        }
+
+        let empty_description = "no description".to_string();
+        let description = match &self.description {
+            Some(desc) => default_if_empty(desc, &empty_description),
+            None => &empty_description,
+        };
+
+        let still_running = "in progress".to_string();
+        let stop = self.stop.as_ref().map(format_time).unwrap_or(still_running);
+
+        write!(f, "{} - {}", format_time(&self.start), stop)?;
+
+        if let Some(day) = format_date(&self.start) {
+            write!(f, " {day}")?;
+        }
+
+        let duration = Duration::seconds(self.duration);
+        if self.stop.is_some() {
+            write!(f, " ({})", format_duration(&duration))?;
+        }
+        if let Some(project_name) = &self.project_name {
+            let label = [
+                self.client_name.as_deref(),
+                Some(project_name.as_str()),
+                self.task_name.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" / ");
+            write!(f, "\t[{label}]")?;
+        }
+        write!(f, "\t{description}")?;
+        if !self.tags.is_empty() {
+            let tags = self
+                .tags
+                .iter()
+                .map(|tag| format!("#{tag}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            write!(f, "\t{tags}")?;
+        }
+        return Ok(());
+    }
+}
+
+pub fn format_duration(duration: &Duration) -> String {
+    let mut result = String::new();
+    let hours = duration.num_hours();
+    if hours > 0 {
+        let hours_part = format!("{} h ", hours);
+        result += hours_part.as_str();
+    }
+
+    let minutes = duration.num_minutes() % 60;
+    let minutes_part = format!("{} min", minutes);
+    result += minutes_part.as_str();
+
+    return result;
+}
+
+fn format_date(datetime: &DateTime<Local>) -> Option<String> {
+    if datetime.date_naive() == Local::now().date_naive() {
+        return None;
+    }
+    if datetime.date_naive() == Local::now().date_naive().pred_opt().unwrap() {
+        return Some("yesterday".to_string());
+    }
+    return Some(datetime.format("%d %b").to_string());
+}
+
+fn format_time(datetime: &DateTime<Local>) -> String {
+    return datetime.format("%H:%M").to_string();
+}
+
+fn default_if_empty<'a>(text: &'a String, default: &'a String) -> &'a String {
+    if text.is_empty() {
+        return default;
+    }
+    return text;
+}
@@ -0,0 +1,464 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Weekday,
+};
+
+const TIME_EXAMPLES: &str = "\"9:15\", \"9am\", \"yesterday 17:00\", \"monday 9:00\", \"-20m\", \"1h30m ago\" or \"2026-10-12T13:00\"";
+const DURATION_EXAMPLES: &str = "\"45\", \"45m\", \"1h30m\" or \"2h\"";
+const DATE_EXAMPLES: &str = "\"today\", \"yesterday\", \"monday\" or \"2026-10-12\"";
+const TIME_RANGE_EXAMPLES: &str =
+    "\"9:00-10:30\", \"yesterday 9:00-10:30\", \"monday 1pm +45m\" or \"2026-10-12 13:00 +45m\"";
+
+pub fn parse_time(input: &str) -> Result<DateTime<Local>> {
+    return parse_time_on(input, Local::now().date_naive());
+}
+
+pub fn parse_time_on(input: &str, default_day: NaiveDate) -> Result<DateTime<Local>> {
+    return parse_time_at(input, default_day, Local::now());
+}
+
+fn parse_time_at<Tz: TimeZone>(
+    input: &str,
+    default_day: NaiveDate,
+    now: DateTime<Tz>,
+) -> Result<DateTime<Tz>>
+where
+    Tz::Offset: Display,
+{
+    let trimmed = input.trim();
+    let lowercase = trimmed.to_lowercase();
+
+    if lowercase == "now" {
+        return Ok(now);
+    }
+    if let Some(offset) = lowercase.strip_prefix('-') {
+        return subtract_duration(now, offset);
+    }
+    if let Some(offset) = lowercase.strip_suffix("ago") {
+        return subtract_duration(now, offset);
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(trimmed) {
+        return Ok(time.with_timezone(&now.timezone()));
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(trimmed, format) {
+            return to_local(time, &now.timezone());
+        }
+    }
+
+    let (day, time) = split_day(&lowercase, now.date_naive());
+    if time.is_empty() {
+        return Err(anyhow!(
+            "Missing time of day in \"{}\". Use e.g. \"{} 9:00\"",
+            trimmed,
+            trimmed
+        ));
+    }
+    if time.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!(
+            "Ambiguous time \"{}\". Use e.g. \"{}:00\", \"{}am\" or \"{}pm\"",
+            trimmed,
+            time,
+            time,
+            time
+        ));
+    }
+    let time = parse_time_of_day(time).map_err(|_| invalid_time(trimmed))?;
+    return to_local(day.unwrap_or(default_day).and_time(time), &now.timezone());
+}
+
+pub fn parse_time_ago(input: &str) -> Result<DateTime<Local>> {
+    return subtract_duration(Local::now(), input);
+}
+
+fn subtract_duration<Tz: TimeZone>(time: DateTime<Tz>, input: &str) -> Result<DateTime<Tz>> {
+    return time
+        .checked_sub_signed(parse_duration(input)?)
+        .ok_or_else(|| invalid_duration(input));
+}
+
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let duration = input.trim().trim_start_matches('+').trim();
+    let invalid_duration = || invalid_duration(input);
+
+    if let Ok(minutes) = duration.parse::<i64>() {
+        if minutes <= 0 {
+            return Err(invalid_duration());
+        }
+        return Duration::try_minutes(minutes).ok_or_else(invalid_duration);
+    }
+
+    let mut total = Duration::zero();
+    let mut chars = duration.chars().filter(|c| !c.is_whitespace()).peekable();
+    while chars.peek().is_some() {
+        let mut number = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+            number.push(c);
+        }
+        let mut unit = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_alphabetic()) {
+            unit.push(c);
+        }
+        let value = number.parse::<i64>().map_err(|_| invalid_duration())?;
+        let part = match unit.to_lowercase().as_str() {
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(value),
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(value),
+            "s" | "sec" | "secs" | "second" | "seconds" => Duration::try_seconds(value),
+            _ => return Err(invalid_duration()),
+        };
+        total = part
+            .and_then(|part| total.checked_add(&part))
+            .ok_or_else(invalid_duration)?;
+    }
+    if total.is_zero() {
+        return Err(invalid_duration());
+    }
+    return Ok(total);
+}
+
+fn parse_date_at(input: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let lowercase = input.trim().to_lowercase();
+    return match split_day(&lowercase, today) {
+        (Some(day), "") => Ok(day),
+        _ => Err(anyhow!(
+            "Invalid date \"{}\". Use e.g. {}",
+            input.trim(),
+            DATE_EXAMPLES
+        )),
+    };
+}
+
+pub fn parse_date_range(from: &str, to: Option<&str>) -> Result<(NaiveDate, NaiveDate)> {
+    return parse_date_range_at(from, to, Local::now().date_naive());
+}
+
+pub fn parse_date_range_at(
+    from: &str,
+    to: Option<&str>,
+    today: NaiveDate,
+) -> Result<(NaiveDate, NaiveDate)> {
+    let from = parse_date_at(from, today)?;
+    let to = match to {
+        Some(to) => parse_date_at(to, today)?,
+        None => today,
+    };
+    if to < from {
+        return Err(anyhow!("The end date cannot be before the start date"));
+    }
+    return Ok((from, to));
+}
+
+pub fn parse_time_range(input: &str) -> Result<(DateTime<Local>, DateTime<Local>)> {
+    return parse_time_range_at(input, Local::now().date_naive(), &Local);
+}
+
+fn parse_time_range_at<Tz: TimeZone>(
+    input: &str,
+    today: NaiveDate,
+    timezone: &Tz,
+) -> Result<(DateTime<Tz>, DateTime<Tz>)>
+where
+    Tz::Offset: Display,
+{
+    let invalid_range = || {
+        anyhow!(
+            "Invalid time range \"{}\". Use e.g. {}",
+            input.trim(),
+            TIME_RANGE_EXAMPLES
+        )
+    };
+
+    let lowercase = input.trim().to_lowercase();
+    let (day, times) = split_day(&lowercase, today);
+    let day = day.unwrap_or(today);
+
+    if let Some((start, duration)) = times.split_once('+') {
+        let start = parse_time_of_day(start).map_err(|_| invalid_range())?;
+        let start = to_local(day.and_time(start), timezone)?;
+        let stop = start
+            .clone()
+            .checked_add_signed(parse_duration(duration)?)
+            .ok_or_else(|| invalid_duration(duration))?;
+        return Ok((start, stop));
+    }
+
+    let (start, stop) = times.split_once('-').ok_or_else(invalid_range)?;
+    let start = parse_time_of_day(start).map_err(|_| invalid_range())?;
+    let stop = parse_time_of_day(stop).map_err(|_| invalid_range())?;
+    if stop == start {
+        return Err(anyhow!(
+            "Time range \"{}\" is empty, start and end are the same",
+            input.trim()
+        ));
+    }
+    let stop_day = if stop < start {
+        day.succ_opt().ok_or_else(invalid_range)?
+    } else {
+        day
+    };
+    return Ok((
+        to_local(day.and_time(start), timezone)?,
+        to_local(stop_day.and_time(stop), timezone)?,
+    ));
+}
+
+fn split_day(input: &str, today: NaiveDate) -> (Option<NaiveDate>, &str) {
+    let (first, rest) = input.split_once(' ').unwrap_or((input, ""));
+    let day = match first {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        value => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .or_else(|| {
+                Weekday::from_str(value)
+                    .ok()
+                    .map(|weekday| last_weekday(today, weekday))
+            }),
+    };
+    if day.is_some() {
+        return (day, rest.trim());
+    }
+    return (None, input.trim());
+}
+
+pub fn last_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days_back =
+        (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    return today - Duration::days(days_back as i64);
+}
+
+fn parse_time_of_day(input: &str) -> Result<NaiveTime> {
+    let time = input.trim().replace(' ', "");
+    let (time, pm) = if let Some(time) = time.strip_suffix("am") {
+        (time, Some(false))
+    } else if let Some(time) = time.strip_suffix("pm") {
+        (time, Some(true))
+    } else {
+        (time.as_str(), None)
+    };
+
+    let (hour, minute) = time.split_once(':').unwrap_or((time, "0"));
+    let mut hour = hour.parse::<u32>()?;
+    let minute = minute.parse::<u32>()?;
+
+    match pm {
+        Some(pm) => {
+            if !(1..=12).contains(&hour) {
+                return Err(anyhow!("Hour must be between 1 and 12 with am/pm"));
+            }
+            hour = hour % 12 + if pm { 12 } else { 0 };
+        }
+        None if !time.contains(':') => {
+            return Err(anyhow!("Ambiguous time of day"));
+        }
+        None => {}
+    }
+
+    return NaiveTime::from_hms_opt(hour, minute, 0).ok_or(anyhow!("Invalid time of day"));
+}
+
+fn to_local<Tz: TimeZone>(time: NaiveDateTime, timezone: &Tz) -> Result<DateTime<Tz>>
+where
+    Tz::Offset: Display,
+{
+    return match timezone.from_local_datetime(&time) {
+        LocalResult::Single(time) => Ok(time),
+        LocalResult::Ambiguous(earlier, later) => Err(anyhow!(
+            "{} happens twice because of a daylight saving time change. Use an ISO-8601 time with an offset, e.g. \"{}\" or \"{}\"",
+            time.format("%Y-%m-%d %H:%M"),
+            earlier.to_rfc3339(),
+            later.to_rfc3339()
+        )),
+        LocalResult::None => Err(anyhow!(
+            "{} does not exist because of a daylight saving time change. Use a time outside of the gap, e.g. \"{}\"",
+            time.format("%Y-%m-%d %H:%M"),
+            (time + Duration::hours(1)).format("%Y-%m-%d %H:%M")
+        )),
+    };
+}
+
+fn invalid_time(input: &str) -> anyhow::Error {
+    return anyhow!("Invalid time \"{}\". Use e.g. {}", input, TIME_EXAMPLES);
+}
+
+fn invalid_duration(input: &str) -> anyhow::Error {
+    return anyhow!(
+        "Invalid duration \"{}\". Use e.g. {}",
+        input.trim(),
+        DURATION_EXAMPLES
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, Local, NaiveDate, TimeZone};
+
+    use super::*;
+
+    fn timezone() -> FixedOffset {
+        return FixedOffset::east_opt(3600).unwrap();
+    }
+
+    fn now() -> DateTime<FixedOffset> {
+        return timezone().with_ymd_and_hms(2026, 3, 25, 12, 0, 0).unwrap();
+    }
+
+    fn today() -> NaiveDate {
+        return now().date_naive();
+    }
+
+    fn local(day: u32, month: u32, hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        return timezone()
+            .with_ymd_and_hms(2026, month, day, hour, minute, 0)
+            .unwrap();
+    }
+
+    fn parse(input: &str) -> Result<DateTime<FixedOffset>> {
+        return parse_time_at(input, today(), now());
+    }
+
+    #[test]
+    fn parses_am_and_pm() {
+        assert_eq!(parse("9am").unwrap(), local(25, 3, 9, 0));
+        assert_eq!(parse("9:15pm").unwrap(), local(25, 3, 21, 15));
+        assert_eq!(parse("12pm").unwrap(), local(25, 3, 12, 0));
+        assert_eq!(parse("12am").unwrap(), local(25, 3, 0, 0));
+        assert!(parse("13pm").is_err());
+    }
+
+    #[test]
+    fn parses_relative_times() {
+        assert_eq!(parse("1h30m ago").unwrap(), local(25, 3, 10, 30));
+        assert_eq!(parse("-20m").unwrap(), local(25, 3, 11, 40));
+        assert_eq!(parse("now").unwrap(), now());
+    }
+
+    #[test]
+    fn parses_day_names() {
+        assert_eq!(parse("yesterday 17:00").unwrap(), local(24, 3, 17, 0));
+        assert_eq!(parse("monday 9:00").unwrap(), local(23, 3, 9, 0));
+        assert_eq!(parse("wednesday 9:00").unwrap(), local(25, 3, 9, 0));
+        assert_eq!(parse("2026-03-01 8:00").unwrap(), local(1, 3, 8, 0));
+    }
+
+    #[test]
+    fn parses_iso_times() {
+        assert_eq!(
+            parse("2026-03-20T13:00:00+00:00").unwrap(),
+            local(20, 3, 14, 0)
+        );
+        assert_eq!(parse("2026-03-20T13:00").unwrap(), local(20, 3, 13, 0));
+        assert_eq!(parse("2026-03-20T13:00:00").unwrap(), local(20, 3, 13, 0));
+    }
+
+    #[test]
+    fn rejects_ambiguous_and_invalid_times() {
+        let error = parse("9").unwrap_err().to_string();
+        assert!(error.contains("Ambiguous"), "{}", error);
+        assert!(parse("monday").is_err());
+        assert!(parse("25:00").is_err());
+        assert!(parse("soon").is_err());
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("45").unwrap(), Duration::minutes(45));
+        assert_eq!(parse_duration("+45").unwrap(), Duration::minutes(45));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("2 hours").unwrap(), Duration::hours(2));
+    }
+
+    #[test]
+    fn rejects_empty_and_negative_durations() {
+        assert!(parse_duration("0").is_err());
+        assert!(parse_duration("-30").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("10x").is_err());
+    }
+
+    #[test]
+    fn rejects_durations_out_of_range() {
+        for input in [
+            "999999999999999999",
+            "99999999999999h",
+            "9999999999999999999s",
+        ] {
+            let error = parse_duration(input).unwrap_err().to_string();
+            assert!(error.starts_with("Invalid duration"), "{}", error);
+        }
+        let error = parse_duration("9223372036854775h9223372036854775h")
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Invalid duration"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_relative_times_out_of_range() {
+        let error = parse("-99999999999h").unwrap_err().to_string();
+        assert!(error.starts_with("Invalid duration"), "{}", error);
+        let error = parse("99999999999h ago").unwrap_err().to_string();
+        assert!(error.starts_with("Invalid duration"), "{}", error);
+        let error = parse_time_range_at("9:00 +99999999999999h", today(), &timezone())
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Invalid duration"), "{}", error);
+        let error = parse_time_range_at("9:00 +99999999999h", today(), &timezone())
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Invalid duration"), "{}", error);
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(parse_date_at("today", today()).unwrap(), today());
+        assert_eq!(
+            parse_date_at("yesterday", today()).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 24).unwrap()
+        );
+        assert!(parse_date_at("yesterday 9:00", today()).is_err());
+    }
+
+    #[test]
+    fn parses_time_ranges() {
+        assert_eq!(
+            parse_time_range_at("yesterday 9:00-10:30", today(), &timezone()).unwrap(),
+            (local(24, 3, 9, 0), local(24, 3, 10, 30))
+        );
+        assert_eq!(
+            parse_time_range_at("monday 1pm +45m", today(), &timezone()).unwrap(),
+            (local(23, 3, 13, 0), local(23, 3, 13, 45))
+        );
+    }
+
+    #[test]
+    fn rolls_time_ranges_over_midnight() {
+        assert_eq!(
+            parse_time_range_at("22:00-1:30", today(), &timezone()).unwrap(),
+            (local(25, 3, 22, 0), local(26, 3, 1, 30))
+        );
+    }
+
+    #[test]
+    fn rejects_empty_and_negative_time_ranges() {
+        assert!(parse_time_range_at("9:00-9:00", today(), &timezone()).is_err());
+        assert!(parse_time_range_at("9:00 +0", today(), &timezone()).is_err());
+        assert!(parse_time_range_at("yesterday 9:00 +-30", today(), &timezone()).is_err());
+    }
+
+    #[test]
+    fn rejects_times_in_daylight_saving_changes() {
+        std::env::set_var("TZ", "CET-1CEST,M3.5.0,M10.5.0/3");
+        let today = NaiveDate::from_ymd_opt(2026, 3, 25).unwrap();
+        let parse = |input| parse_time_at(input, today, Local::now());
+
+        let error = parse("2026-03-29 2:30").unwrap_err().to_string();
+        assert!(error.contains("does not exist"), "{}", error);
+        let error = parse("2026-10-25 2:30").unwrap_err().to_string();
+        assert!(error.contains("happens twice"), "{}", error);
+    }
+}
@@ -1,340 +1,887 @@
-use std::{collections::HashMap, fmt::Display};
-
-use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc, Weekday};
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    api_client::{ApiClient, Project, TimeEntryDto},
-    config::Config,
+    api_client::{
+        ApiClient, Client, Project, ProjectDetails, ProjectFields, Tag, Task, TimeEntryChanges,
+        TimeEntryDto, TimeEntryFields, Workspace,
+    },
+    api_error::ApiError,
+    cache,
+    config::{update_config, Config},
+    git,
+    journal::{self, PendingOperation},
+    summary::{self, GroupBy, SortBy, Summary, SummaryPeriod},
+    time_entry::TimeEntry,
+    time_parser, undo,
 };
 
+const DEFAULT_CACHE_TTL_SECONDS: u64 = 60;
+
 pub struct TogglClient {
     api_client: ApiClient,
     config: Config,
+    cache_ttl: u64,
+}
+
+pub enum DeleteResult {
+    NotFound,
+    Cancelled,
+    Deleted(Box<TimeEntry>),
+}
+
+pub struct EntryDetails {
+    pub description: Option<String>,
+    pub project: Option<String>,
+    pub task: Option<String>,
+    pub tags: Vec<String>,
+    pub billable: Option<bool>,
+}
+
+pub enum ChangeResult<T> {
+    Applied(T),
+    Queued(T),
+}
+
+impl<T> ChangeResult<T> {
+    pub fn as_ref(&self) -> ChangeResult<&T> {
+        return match self {
+            ChangeResult::Applied(value) => ChangeResult::Applied(value),
+            ChangeResult::Queued(value) => ChangeResult::Queued(value),
+        };
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ChangeResult<U> {
+        return match self {
+            ChangeResult::Applied(value) => ChangeResult::Applied(f(value)),
+            ChangeResult::Queued(value) => ChangeResult::Queued(f(value)),
+        };
+    }
+}
+
+pub struct SyncReport {
+    pub synced: Vec<TimeEntry>,
+    pub conflicts: Vec<String>,
+}
+
+impl SyncReport {
+    pub fn is_empty(&self) -> bool {
+        return self.synced.is_empty() && self.conflicts.is_empty();
+    }
 }
 
 impl TogglClient {
-    pub fn new(api_token: Option<String>, config: Config) -> Result<TogglClient> {
+    pub fn new(api_token: Option<String>, config: Config, refresh: bool) -> Result<TogglClient> {
+        let cache_ttl = if refresh {
+            0
+        } else {
+            config.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL_SECONDS)
+        };
         return Ok(TogglClient {
-            api_client: ApiClient::new(api_token.as_deref())?,
+            api_client: ApiClient::new(api_token.as_deref(), &config)?,
             config,
+            cache_ttl,
         });
     }
 
-    pub fn print_recent_entries(&self) -> Result<()> {
-        let time_entries = self.get_recent_entries()?;
-        let today = Local::now().date_naive();
-        let today_entries = time_entries
-            .iter()
-            .filter(|entry| entry.start.date_naive() == today)
+    pub fn get_summary(
+        &self,
+        period: SummaryPeriod,
+        billable_only: bool,
+        group_by: &[GroupBy],
+        sort_by: SortBy,
+    ) -> Result<Summary> {
+        let (from, to) = period.date_range(Local::now().date_naive(), self.week_start()?)?;
+        let time_entries = self
+            .fetch_entries_between(from, to)?
+            .into_iter()
+            .filter(|entry| entry.billable || !billable_only)
             .collect::<Vec<_>>();
+        return Ok(summary::summarize(
+            &time_entries,
+            from,
+            to,
+            group_by,
+            sort_by,
+        ));
+    }
 
-        if today_entries.len() > 0 {
-            print!(" -- Today -- ");
-            let total = today_entries
-                .iter()
-                .map(|entry| {
-                    if entry.stop.is_some() {
-                        entry.duration
-                    } else {
-                        Utc::now().timestamp() - entry.start.timestamp()
-                    }
-                })
-                .sum::<i64>();
-            let duration = Duration::seconds(total);
-            let hours = duration.num_hours();
-            let minutes = duration.num_minutes() - hours * 60;
-            println!("⌛{} hours {:02} minutes", hours, minutes);
-        }
+    pub fn get_recent_entries(&self) -> Result<Vec<TimeEntry>> {
+        return self
+            .cached("recent_entries", || self.api_client.get_recent_entries())?
+            .iter()
+            .map(|dto| TimeEntry::from_dto(dto, &self.config))
+            .collect();
+    }
 
-        for time_entry in today_entries {
-            println!("{}", time_entry);
-        }
+    pub fn get_entries_between(&self, from: &str, to: Option<&str>) -> Result<Vec<TimeEntry>> {
+        let (from, to) = time_parser::parse_date_range(from, to)?;
+        return self.fetch_entries_between(from, to);
+    }
 
-        let older_entries = time_entries
+    fn fetch_entries_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<TimeEntry>> {
+        let key = format!("entries_between/{}/{}", from, to);
+        let mut time_entries = self
+            .cached(&key, || self.api_client.get_entries_between(from, to))?
             .iter()
-            .filter(|entry| entry.start.date_naive() != today)
-            .take(10)
-            .collect::<Vec<_>>();
+            .map(|dto| TimeEntry::from_dto(dto, &self.config))
+            .collect::<Result<Vec<TimeEntry>>>()?;
+        time_entries.sort_by_key(|entry| entry.start);
+        return Ok(time_entries);
+    }
+
+    pub fn get_current_entry(&self) -> Result<Option<TimeEntry>> {
+        return self
+            .cached("current_entry", || self.api_client.get_current_entry())?
+            .map(|dto| TimeEntry::from_dto(&dto, &self.config))
+            .transpose();
+    }
 
-        if older_entries.len() > 0 {
-            println!(" -- Older -- ");
-            for time_entry in older_entries {
-                println!("{}", time_entry);
+    pub fn stop_current_entry(&self) -> Result<ChangeResult<Option<TimeEntry>>> {
+        cache::clear()?;
+        return match self.api_client.stop_current_entry() {
+            Err(error) if is_offline(&error) => {
+                let stop = Utc::now();
+                let (queued_entry, entry_start) = match journal::load()?.pop() {
+                    Some(PendingOperation::Start { fields, start }) => (
+                        Some(TimeEntry::from_fields(
+                            &fields,
+                            start.parse()?,
+                            Some(stop),
+                            &self.config,
+                        )),
+                        Some(start),
+                    ),
+                    _ => (None, None),
+                };
+                journal::record(PendingOperation::Stop {
+                    stop: stop.to_rfc3339(),
+                    entry_start,
+                })?;
+                Ok(ChangeResult::Queued(queued_entry))
             }
+            result => Ok(ChangeResult::Applied(
+                result?
+                    .map(|dto| TimeEntry::from_dto(&dto, &self.config))
+                    .transpose()?,
+            )),
+        };
+    }
+
+    pub fn restart(&self) -> Result<Option<TimeEntry>> {
+        cache::clear()?;
+        let recent_entries = self.api_client.get_recent_entries()?;
+        let last_one = recent_entries.first();
+        if let Some(last_one) = last_one {
+            let started = self.api_client.restart(last_one)?;
+            return Ok(Some(TimeEntry::from_dto(&started, &self.config)?));
         }
+        return Ok(None);
+    }
 
-        return Ok(());
+    pub fn switch(&self) -> Result<Option<TimeEntry>> {
+        cache::clear()?;
+        let recent_entries = self.api_client.get_recent_entries()?;
+        let prev = recent_entries.iter().find(|entry| entry.stop.is_some());
+        if let Some(prev) = prev {
+            let started = self.api_client.restart(prev)?;
+            return Ok(Some(TimeEntry::from_dto(&started, &self.config)?));
+        }
+        return Ok(None);
     }
 
-    pub fn print_day_summary(&self, days_before: Option<u8>) -> Result<()> {
-        let today = Local::now();
-        let day = (today - Duration::days(days_before.unwrap_or(0) as i64)).date_naive();
-        let time_entries = self.get_entries_from_day(day)?;
+    pub fn start(
+        &mut self,
+        details: EntryDetails,
+        start: Option<String>,
+        time: Option<String>,
+    ) -> Result<ChangeResult<TimeEntry>> {
+        let start = if let Some(start) = start {
+            time_parser::parse_time(&start)?
+        } else if let Some(time) = time {
+            time_parser::parse_time_ago(&time)?
+        } else {
+            Local::now()
+        };
+        let details = self.with_branch_defaults(details);
+        let fields = self.entry_fields(details)?;
+        let start = start.with_timezone(&Utc);
+        cache::clear()?;
+        return match self.api_client.start(fields.clone(), start) {
+            Err(error) if is_offline(&error) => {
+                let queued_entry = TimeEntry::from_fields(&fields, start, None, &self.config);
+                journal::record(PendingOperation::Start {
+                    fields,
+                    start: start.to_rfc3339(),
+                })?;
+                Ok(ChangeResult::Queued(queued_entry))
+            }
+            Err(error) if is_timeout(&error) => Err(unconfirmed_creation(error)),
+            result => Ok(ChangeResult::Applied(TimeEntry::from_dto(
+                &result?,
+                &self.config,
+            )?)),
+        };
+    }
 
-        if time_entries.len() > 0 {
-            if day == today.date_naive() {
-                print!(" -- Today -- ");
-            } else {
-                print!(" -- {} -- ", day);
+    pub fn add(
+        &mut self,
+        time_range: String,
+        details: EntryDetails,
+    ) -> Result<ChangeResult<TimeEntry>> {
+        let (start, stop) = time_parser::parse_time_range(&time_range)?;
+        let fields = self.entry_fields(details)?;
+        let (start, stop) = (start.with_timezone(&Utc), stop.with_timezone(&Utc));
+        cache::clear()?;
+        return match self.api_client.add(fields.clone(), start, stop) {
+            Err(error) if is_offline(&error) => {
+                let queued_entry = TimeEntry::from_fields(&fields, start, Some(stop), &self.config);
+                journal::record(PendingOperation::Add {
+                    fields,
+                    start: start.to_rfc3339(),
+                    stop: stop.to_rfc3339(),
+                })?;
+                Ok(ChangeResult::Queued(queued_entry))
             }
-            let total = time_entries
-                .iter()
-                .map(|entry| {
-                    if entry.stop.is_some() {
-                        entry.duration
-                    } else {
-                        Utc::now().timestamp() - entry.start.timestamp()
-                    }
-                })
-                .sum::<i64>();
-            let duration = Duration::seconds(total);
-            let hours = duration.num_hours();
-            let minutes = duration.num_minutes() - hours * 60;
-            println!("⌛{} hours {:02} minutes", hours, minutes);
-        }
+            Err(error) if is_timeout(&error) => Err(unconfirmed_creation(error)),
+            result => Ok(ChangeResult::Applied(TimeEntry::from_dto(
+                &result?,
+                &self.config,
+            )?)),
+        };
+    }
 
-        let mut summed_entries = HashMap::new();
-        time_entries.iter().for_each(|entry| {
-            if entry.stop.is_none() {
-                return;
+    fn with_branch_defaults(&self, mut details: EntryDetails) -> EntryDetails {
+        let branch = match git::current_branch() {
+            Some(branch) => branch,
+            None => return details,
+        };
+        let rule = self.config.branches.iter().flatten().find(|rule| {
+            return git::matches_pattern(&rule.pattern, &branch);
+        });
+        if let Some(rule) = rule {
+            details.project = details.project.or(rule.project.clone());
+            if details.tags.is_empty() {
+                details.tags = rule.tags.clone().unwrap_or_default();
             }
-            let description = entry.description.clone().unwrap_or(String::from(""));
-            let project = entry.project_name.clone().unwrap_or(String::from(""));
-            let duration = entry.duration;
-            summed_entries
-                .entry(description)
-                .and_modify(|v: &mut (String, i64)| (*v).1 += duration)
-                .or_insert((project, duration));
+        }
+        if details.description.is_none() {
+            details.description = match &self.config.description_template {
+                Some(template) => git::render_template(template, &branch),
+                None => git::default_description(&branch),
+            };
+        }
+        return details;
+    }
+
+    fn entry_fields(&mut self, details: EntryDetails) -> Result<TimeEntryFields> {
+        let workspace_id = self.workspace_id()?;
+        let project_id = self.resolve_optional_project(details.project)?;
+        let task = details
+            .task
+            .map(|task| self.resolve_task(&task, project_id))
+            .transpose()?;
+        let project_id = project_id
+            .or(task.as_ref().map(|task| task.project_id))
+            .or(self.config.project_id);
+        return Ok(TimeEntryFields {
+            workspace_id,
+            description: details.description,
+            project_id,
+            task_id: task.map(|task| task.id),
+            tags: none_if_empty(details.tags),
+            billable: details
+                .billable
+                .unwrap_or_else(|| self.is_billable_by_default(project_id)),
         });
+    }
 
-        for (key, (project, time)) in summed_entries {
-            let duration = Duration::seconds(time);
-            println!("{}\t[{}]\t{}", format_duration(&duration), project, key);
+    pub fn sync(&self) -> Result<SyncReport> {
+        let operations = journal::load()?;
+        if !operations.is_empty() {
+            cache::clear()?;
         }
-
-        return Ok(());
+        let mut report = SyncReport {
+            synced: vec![],
+            conflicts: vec![],
+        };
+        for (index, operation) in operations.iter().enumerate() {
+            match self.replay(operation, &mut report) {
+                Err(error) if is_retryable(&error) => {
+                    journal::save(&operations[index..])?;
+                    return Err(error.context("Could not sync queued time entry changes"));
+                }
+                Err(error) => report.conflicts.push(format!(
+                    "Dropped {}: {:#}",
+                    self.describe_operation(operation),
+                    error
+                )),
+                Ok(()) => {}
+            }
+        }
+        journal::save(&[])?;
+        return Ok(report);
     }
 
-    fn get_recent_entries(&self) -> Result<Vec<TimeEntry>> {
-        return self.api_client.get_recent_entries().and_then(|vec| {
-            vec.into_iter()
-                .map(|dto| TimeEntry::from_dto(&dto, &self.config))
-                .collect::<Result<Vec<TimeEntry>>>()
-        });
+    fn replay(&self, operation: &PendingOperation, report: &mut SyncReport) -> Result<()> {
+        match operation {
+            PendingOperation::Start { fields, start } => {
+                let start: DateTime<Utc> = start.parse()?;
+                let queued_entry = TimeEntry::from_fields(fields, start, None, &self.config);
+                if let Some(current) = self.api_client.get_current_entry()? {
+                    if current.start.parse::<DateTime<Utc>>()? > start {
+                        report.conflicts.push(format!(
+                            "Not started {}: another time entry was started meanwhile: {}",
+                            queued_entry,
+                            self.to_time_entry(&current)?
+                        ));
+                        return Ok(());
+                    }
+                    let changes = TimeEntryChanges {
+                        stop: Some(start),
+                        ..Default::default()
+                    };
+                    self.api_client.edit(&current, changes)?;
+                }
+                let started = self.api_client.start(fields.clone(), start)?;
+                report.synced.push(self.to_time_entry(&started)?);
+            }
+            PendingOperation::Stop { stop, entry_start } => {
+                let stop: DateTime<Utc> = stop.parse()?;
+                let Some(current) = self.api_client.get_current_entry()? else {
+                    report.conflicts.push(format!(
+                        "Not stopped at {}: the time entry was already stopped elsewhere",
+                        stop.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                    ));
+                    return Ok(());
+                };
+                let current_start = current.start.parse::<DateTime<Utc>>()?;
+                // A stop queued after a queued start must only stop the entry that start created,
+                // Toggl stores start times with second precision.
+                let started_meanwhile = match entry_start {
+                    Some(entry_start) => {
+                        (current_start - entry_start.parse::<DateTime<Utc>>()?).num_seconds() != 0
+                    }
+                    None => current_start > stop,
+                };
+                if started_meanwhile {
+                    report.conflicts.push(format!(
+                        "Not stopped at {}: another time entry was started meanwhile: {}",
+                        stop.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                        self.to_time_entry(&current)?
+                    ));
+                    return Ok(());
+                }
+                let changes = TimeEntryChanges {
+                    stop: Some(stop),
+                    ..Default::default()
+                };
+                let stopped = self.api_client.edit(&current, changes)?;
+                report.synced.push(self.to_time_entry(&stopped)?);
+            }
+            PendingOperation::Add {
+                fields,
+                start,
+                stop,
+            } => {
+                let added = self
+                    .api_client
+                    .add(fields.clone(), start.parse()?, stop.parse()?)?;
+                report.synced.push(self.to_time_entry(&added)?);
+            }
+        }
+        return Ok(());
     }
 
-    fn get_entries_from_day(&self, day: NaiveDate) -> Result<Vec<TimeEntry>> {
-        return self.api_client.get_entries_from_day(day).and_then(|vec| {
-            vec.into_iter()
-                .map(|dto| TimeEntry::from_dto(&dto, &self.config))
-                .collect::<Result<Vec<TimeEntry>>>()
-        });
+    fn describe_operation(&self, operation: &PendingOperation) -> String {
+        let format_time = |time: &str| {
+            return time
+                .parse::<DateTime<Utc>>()
+                .map(|time| {
+                    time.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_else(|_| time.to_string());
+        };
+        return match operation {
+            PendingOperation::Start { fields, start } => format!(
+                "queued start of \"{}\" at {}",
+                fields.description.as_deref().unwrap_or("no description"),
+                format_time(start)
+            ),
+            PendingOperation::Stop { stop, .. } => format!("queued stop at {}", format_time(stop)),
+            PendingOperation::Add {
+                fields,
+                start,
+                stop,
+            } => format!(
+                "queued entry \"{}\" from {} to {}",
+                fields.description.as_deref().unwrap_or("no description"),
+                format_time(start),
+                format_time(stop)
+            ),
+        };
     }
 
-    pub fn print_current_entry(&self) -> Result<()> {
-        let maybe_time_entry = self.get_current_entry()?;
-        if let Some(time_entry) = maybe_time_entry {
-            println!("{}", time_entry);
-        } else {
-            println!("There are no active time entries");
+    fn cached<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &str,
+        fetch: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        if let Some(value) = cache::get(key, self.cache_ttl)? {
+            return Ok(value);
         }
+        let value = fetch()?;
+        cache::put(key, &value)?;
+        return Ok(value);
+    }
 
-        return Ok(());
+    fn to_time_entry(&self, dto: &TimeEntryDto) -> Result<TimeEntry> {
+        return TimeEntry::from_dto(dto, &self.config);
     }
 
-    fn get_current_entry(&self) -> Result<Option<TimeEntry>> {
-        return self
-            .api_client
-            .get_current_entry()?
-            .map(|dto| TimeEntry::from_dto(&dto, &self.config))
-            .transpose();
+    pub fn edit(
+        &mut self,
+        id: Option<u64>,
+        description: Option<String>,
+        project: Option<String>,
+        start: Option<String>,
+        stop: Option<String>,
+        tags: Vec<String>,
+    ) -> Result<Option<TimeEntry>> {
+        let time_entry = match id {
+            Some(id) => self.api_client.get_entry(id)?,
+            None => match self.api_client.get_current_entry()? {
+                Some(time_entry) => time_entry,
+                None => return Ok(None),
+            },
+        };
+        let project_id = self.resolve_optional_project(project)?;
+        let day = time_entry.start.parse::<DateTime<Local>>()?.date_naive();
+        let parse_time = |time: String| {
+            time_parser::parse_time_on(&time, day).map(|time| time.with_timezone(&Utc))
+        };
+        cache::clear()?;
+        let changes = TimeEntryChanges {
+            description,
+            project_id,
+            tags: none_if_empty(tags),
+            start: start.map(parse_time).transpose()?,
+            stop: stop.map(parse_time).transpose()?,
+        };
+        let updated_entry = self.api_client.edit(&time_entry, changes)?;
+        return Ok(Some(TimeEntry::from_dto(&updated_entry, &self.config)?));
     }
 
-    pub fn stop_current_entry(&self) -> Result<()> {
-        if let Some(stopped_entry) = self.api_client.stop_current_entry()? {
-            println!(
-                "Stopped time entry: {}",
-                TimeEntry::from_dto(&stopped_entry, &self.config)?
-            );
+    pub fn delete(
+        &self,
+        id: Option<u64>,
+        last: bool,
+        confirm: impl FnOnce(&TimeEntry) -> Result<bool>,
+    ) -> Result<DeleteResult> {
+        let time_entry = if let Some(id) = id {
+            Some(self.api_client.get_entry(id)?)
+        } else if last {
+            self.api_client.get_recent_entries()?.into_iter().next()
         } else {
-            println!("There are no active time entries");
+            self.api_client.get_current_entry()?
+        };
+        let Some(time_entry) = time_entry else {
+            return Ok(DeleteResult::NotFound);
+        };
+
+        let deleted_entry = TimeEntry::from_dto(&time_entry, &self.config)?;
+        if !confirm(&deleted_entry)? {
+            return Ok(DeleteResult::Cancelled);
         }
 
-        return Ok(());
+        cache::clear()?;
+        self.api_client.delete_time_entry(&time_entry)?;
+        undo::save_deleted_entry(time_entry)?;
+        return Ok(DeleteResult::Deleted(Box::new(deleted_entry)));
     }
 
-    pub fn restart(&self) -> Result<()> {
-        let recent_entries = self.api_client.get_recent_entries()?;
-        let last_one = recent_entries.first();
-        if let Some(last_one) = last_one {
-            let started = self.api_client.restart(&last_one)?;
-            println!(
-                "Time entry started: {}",
-                TimeEntry::from_dto(&started, &self.config)?
-            );
-        } else {
-            println!("There are no recent entries");
+    pub fn undo(&self) -> Result<Option<TimeEntry>> {
+        if let Some(time_entry) = undo::load_deleted_entry()? {
+            cache::clear()?;
+            let restored = self.api_client.recreate(&time_entry)?;
+            undo::clear_deleted_entry()?;
+            return Ok(Some(TimeEntry::from_dto(&restored, &self.config)?));
         }
+        return Ok(None);
+    }
+
+    pub fn get_default_workspace_id(&self) -> Result<u64> {
+        return self.cached("default_workspace_id", || {
+            self.api_client.get_default_workspace_id()
+        });
+    }
+
+    pub fn get_workspaces(&self) -> Result<Vec<Workspace>> {
+        let workspaces = self.cached("workspaces", || self.api_client.get_workspaces())?;
+        update_config(
+            true,
+            Config {
+                workspaces: Some(workspaces.clone()),
+                ..Default::default()
+            },
+        )?;
+        return Ok(workspaces);
+    }
+
+    pub fn select_workspace(&mut self, workspace: &str) -> Result<()> {
+        let workspace_id = self.resolve_workspace(workspace)?;
+        self.config.workspace_id = Some(workspace_id);
         return Ok(());
     }
 
-    pub fn switch(&self) -> Result<()> {
-        let recent_entries = self.api_client.get_recent_entries()?;
-        let prev = recent_entries.iter().find(|entry| entry.stop.is_some());
-        if let Some(prev) = prev {
-            let started = self.api_client.restart(&prev)?;
-            println!(
-                "Time entry started: {}",
-                TimeEntry::from_dto(&started, &self.config)?
-            );
-        } else {
-            println!("There are no recent entries");
+    fn resolve_workspace(&mut self, workspace: &str) -> Result<u64> {
+        if let Ok(workspace_id) = workspace.parse::<u64>() {
+            return Ok(workspace_id);
         }
-        return Ok(());
+
+        let cached_workspaces = self.config.workspaces.clone().unwrap_or_default();
+        if let Some(workspace_id) =
+            find_id("Workspace", workspace, workspace_names(&cached_workspaces))?
+        {
+            return Ok(workspace_id);
+        }
+
+        let workspaces = self.get_workspaces()?;
+        let workspace_id = find_id("Workspace", workspace, workspace_names(&workspaces))?;
+        self.config.workspaces = Some(workspaces);
+        return workspace_id.with_context(|| format!("Workspace \"{}\" not found", workspace));
     }
 
-    pub fn start(
-        &self,
-        description: Option<String>,
-        project_id: Option<u64>,
-        start: Option<String>,
-        time: Option<String>,
-    ) -> Result<()> {
-        let workspace_id = self
-            .config
-            .workspace_id
-            .context("workspace id should be set")?;
-        let started_entry = self.api_client.start(
-            workspace_id,
-            description,
-            project_id.or_else(|| self.config.project_id),
-            start,
-            time,
+    pub fn get_clients(&mut self) -> Result<Vec<Client>> {
+        let clients = self.cached("clients", || self.api_client.get_clients())?;
+        update_config(
+            true,
+            Config {
+                clients: Some(clients.clone()),
+                ..Default::default()
+            },
         )?;
-        println!(
-            "Time entry started: {}",
-            TimeEntry::from_dto(&started_entry, &self.config)?
-        );
-        return Ok(());
+        self.config.clients = Some(clients.clone());
+        return Ok(clients);
     }
 
-    pub fn print_default_workspace_id(&self) -> Result<()> {
-        let id = self.api_client.get_default_workspace_id()?;
-        println!("Workspace id {}", id);
-        return Ok(());
+    pub fn get_tasks(&mut self, project: Option<String>) -> Result<Vec<Task>> {
+        let project_id = self.resolve_optional_project(project)?;
+        let tasks = self.update_cached_tasks()?;
+        return Ok(tasks
+            .into_iter()
+            .filter(|task| project_id.is_none_or(|project_id| task.project_id == project_id))
+            .collect());
     }
 
-    pub fn print_projects(&self) -> Result<()> {
-        self.api_client
-            .get_projects()?
-            .iter()
-            .for_each(|project| println!("[{}] {}", project.id, project.name));
-        return Ok(());
+    fn update_cached_tasks(&mut self) -> Result<Vec<Task>> {
+        let tasks = self.api_client.get_tasks()?;
+        update_config(
+            true,
+            Config {
+                tasks: Some(tasks.clone()),
+                ..Default::default()
+            },
+        )?;
+        self.config.tasks = Some(tasks.clone());
+        return Ok(tasks);
     }
-}
 
-struct TimeEntry {
-    _id: u64,
-    _workspace_id: u64,
-    description: Option<String>,
-    _project_id: Option<u64>,
-    project_name: Option<String>,
-    start: DateTime<Local>,
-    stop: Option<DateTime<Local>>,
-    duration: i64,
-}
+    fn resolve_task(&mut self, task: &str, project_id: Option<u64>) -> Result<Task> {
+        let cached_tasks = self.config.tasks.clone().unwrap_or_default();
+        if let Some(found) = find_task(task, project_id, &cached_tasks)? {
+            return Ok(found);
+        }
 
-impl TimeEntry {
-    fn from_dto(dto: &TimeEntryDto, config: &Config) -> Result<TimeEntry> {
-        return Ok(TimeEntry {
-            _id: dto.id,
-            _workspace_id: dto.workspace_id,
-            description: dto.description.to_owned(),
-            _project_id: dto.project_id,
-            project_name: find_project_name(dto.project_id, &config.projects),
-            start: dto.start.parse()?,
-            stop: dto.stop.to_owned().map(|value| value.parse()).transpose()?,
-            duration: dto.duration,
-        });
+        let tasks = self.update_cached_tasks()?;
+        return find_task(task, project_id, &tasks)?
+            .with_context(|| format!("Task \"{}\" not found", task));
+    }
+
+    pub fn get_projects(&self) -> Result<Vec<Project>> {
+        return self.cached("projects", || self.api_client.get_projects());
+    }
+
+    pub fn create_project(
+        &mut self,
+        name: String,
+        client_id: Option<u64>,
+        color: Option<String>,
+        billable: Option<bool>,
+        estimated_hours: Option<u32>,
+    ) -> Result<ProjectDetails> {
+        let fields = ProjectFields {
+            workspace_id: self.workspace_id()?,
+            name,
+            client_id,
+            color,
+            billable,
+            estimated_hours,
+        };
+        let project = self.api_client.create_project(fields)?;
+        self.update_cached_projects()?;
+        return Ok(project);
+    }
+
+    pub fn archive_project(&mut self, project: &str) -> Result<ProjectDetails> {
+        let (workspace_id, project_id) = self.locate_project(project)?;
+        let project =
+            self.api_client
+                .update_project(workspace_id, project_id, None, None, Some(false))?;
+        self.update_cached_projects()?;
+        return Ok(project);
+    }
+
+    pub fn rename_project(&mut self, project: &str, new_name: &str) -> Result<ProjectDetails> {
+        let (workspace_id, project_id) = self.locate_project(project)?;
+        let project =
+            self.api_client
+                .update_project(workspace_id, project_id, Some(new_name), None, None)?;
+        self.update_cached_projects()?;
+        return Ok(project);
     }
-}
 
-fn find_project_name(project_id: Option<u64>, projects: &Option<Vec<Project>>) -> Option<String> {
-    project_id.and_then(|project_id| {
-        projects.as_ref().and_then(|projects| {
-            projects
+    pub fn set_project_color(&mut self, project: &str, color: &str) -> Result<ProjectDetails> {
+        let (workspace_id, project_id) = self.locate_project(project)?;
+        let project =
+            self.api_client
+                .update_project(workspace_id, project_id, None, Some(color), None)?;
+        self.update_cached_projects()?;
+        return Ok(project);
+    }
+
+    pub fn show_project(&mut self, project: &str) -> Result<ProjectDetails> {
+        let (workspace_id, project_id) = self.locate_project(project)?;
+        return self.api_client.get_project(workspace_id, project_id);
+    }
+
+    fn locate_project(&mut self, project: &str) -> Result<(u64, u64)> {
+        let project_id = self.resolve_project(project)?;
+        let find_workspace_id = |projects: &Option<Vec<Project>>| {
+            return projects
                 .iter()
+                .flatten()
                 .find(|project| project.id == project_id)
-                .map(|project| project.name.to_owned())
-        })
-    })
-}
+                .and_then(|project| project.workspace_id);
+        };
+        if let Some(workspace_id) = find_workspace_id(&self.config.projects) {
+            return Ok((workspace_id, project_id));
+        }
 
-impl Display for TimeEntry {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let empty_description = "no description".to_string();
-        let description = match &self.description {
-            Some(desc) => default_if_empty(desc, &empty_description),
-            None => &empty_description,
+        self.update_cached_projects()?;
+        let workspace_id = match find_workspace_id(&self.config.projects) {
+            Some(workspace_id) => workspace_id,
+            None => self.workspace_id()?,
         };
+        return Ok((workspace_id, project_id));
+    }
+
+    fn update_cached_projects(&mut self) -> Result<()> {
+        cache::clear()?;
+        let projects = self.api_client.get_projects()?;
+        update_config(
+            true,
+            Config {
+                projects: Some(projects.clone()),
+                ..Default::default()
+            },
+        )?;
+        self.config.projects = Some(projects);
+        return Ok(());
+    }
+
+    pub fn get_tags(&self) -> Result<Vec<Tag>> {
+        let workspace_id = self.workspace_id()?;
+        return self.cached(&format!("tags/{}", workspace_id), || {
+            self.api_client.get_tags(workspace_id)
+        });
+    }
 
-        let still_running = "in progress".to_string();
-        let stop = self
-            .stop
-            .as_ref()
-            .map(|value| format_time(&value))
-            .unwrap_or(still_running);
+    pub fn create_tag(&self, name: &str) -> Result<Tag> {
+        let workspace_id = self.workspace_id()?;
+        let tag = self.api_client.create_tag(workspace_id, name)?;
+        cache::clear()?;
+        return Ok(tag);
+    }
 
-        write!(f, "{} - {}", format_time(&self.start), stop)?;
+    pub fn rename_tag(&self, name: &str, new_name: &str) -> Result<Tag> {
+        let workspace_id = self.workspace_id()?;
+        let tag = self
+            .api_client
+            .get_tags(workspace_id)?
+            .into_iter()
+            .find(|tag| tag.name == name)
+            .with_context(|| format!("Tag \"{}\" not found", name))?;
+        let tag = self.api_client.rename_tag(workspace_id, tag.id, new_name)?;
+        cache::clear()?;
+        return Ok(tag);
+    }
 
-        if let Some(day) = format_date(&self.start) {
-            write!(f, " {day}")?;
+    pub fn resolve_project(&mut self, project: &str) -> Result<u64> {
+        if let Ok(project_id) = project.parse::<u64>() {
+            return Ok(project_id);
         }
 
-        let duration = Duration::seconds(self.duration);
-        if let Some(_) = &self.stop {
-            write!(f, " ({})", format_duration(&duration))?;
-        }
-        if let Some(project_name) = &self.project_name {
-            write!(f, "\t[{project_name}]")?;
+        let cached_projects = self.config.projects.clone().unwrap_or_default();
+        if let Some(project_id) = find_id("Project", project, project_names(&cached_projects))? {
+            return Ok(project_id);
         }
-        return write!(f, "\t{description}");
+
+        let projects = self.api_client.get_projects()?;
+        update_config(
+            true,
+            Config {
+                projects: Some(projects.clone()),
+                ..Default::default()
+            },
+        )?;
+        let project_id = find_id("Project", project, project_names(&projects))?;
+        self.config.projects = Some(projects);
+        return project_id.with_context(|| format!("Project \"{}\" not found", project));
     }
-}
 
-fn format_duration(duration: &Duration) -> String {
-    let mut result = String::new();
-    let hours = duration.num_hours();
-    if hours > 0 {
-        let hours_part = format!("{} h ", hours);
-        result += hours_part.as_str();
+    fn resolve_optional_project(&mut self, project: Option<String>) -> Result<Option<u64>> {
+        return project
+            .map(|project| self.resolve_project(&project))
+            .transpose();
     }
 
-    let minutes = duration.num_minutes() % 60;
-    let minutes_part = format!("{} min", minutes);
-    result += minutes_part.as_str();
+    fn is_billable_by_default(&self, project_id: Option<u64>) -> bool {
+        return project_id
+            .and_then(|project_id| {
+                self.config.projects.as_ref().and_then(|projects| {
+                    projects
+                        .iter()
+                        .find(|project| project.id == project_id)
+                        .and_then(|project| project.billable)
+                })
+            })
+            .unwrap_or(false);
+    }
 
-    return result;
-}
+    fn week_start(&self) -> Result<Weekday> {
+        return match &self.config.week_start {
+            Some(week_start) => week_start
+                .parse::<Weekday>()
+                .map_err(|_| anyhow!("Invalid week start \"{}\" in config", week_start)),
+            None => Ok(Weekday::Mon),
+        };
+    }
 
-fn format_date(datetime: &DateTime<Local>) -> Option<String> {
-    if datetime.date_naive() == Local::now().date_naive() {
-        return None;
+    fn workspace_id(&self) -> Result<u64> {
+        return self
+            .config
+            .workspace_id
+            .context("workspace id should be set");
     }
-    if datetime.date_naive() == Local::now().date_naive().pred_opt().unwrap() {
-        return Some("yesterday".to_string());
+}
+
+fn find_id<'a>(
+    kind: &str,
+    query: &str,
+    items: impl Iterator<Item = (u64, &'a str)>,
+) -> Result<Option<u64>> {
+    let items = items.collect::<Vec<_>>();
+    let needle = query.to_lowercase();
+    let matchers: [fn(&str, &str) -> bool; 3] = [
+        |name, query| name == query,
+        |name, query| name.starts_with(query),
+        |name, query| name.contains(query),
+    ];
+    for matches in matchers {
+        let candidates = items
+            .iter()
+            .filter(|(_, name)| matches(&name.to_lowercase(), &needle))
+            .collect::<Vec<_>>();
+        match candidates.as_slice() {
+            [] => continue,
+            [(id, _)] => return Ok(Some(*id)),
+            _ => {
+                let candidates = candidates
+                    .iter()
+                    .map(|(id, name)| format!("  [{}] {}", id, name))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                return Err(anyhow!(
+                    "{} \"{}\" is ambiguous. Matches:\n{}",
+                    kind,
+                    query,
+                    candidates
+                ));
+            }
+        }
     }
-    return Some(datetime.format("%d %b").to_string());
+    return Ok(None);
+}
+
+fn find_task(query: &str, project_id: Option<u64>, tasks: &[Task]) -> Result<Option<Task>> {
+    let tasks = tasks
+        .iter()
+        .filter(|task| project_id.is_none_or(|project_id| task.project_id == project_id))
+        .collect::<Vec<_>>();
+    let task_id = match query.parse::<u64>() {
+        Ok(task_id) => Some(task_id),
+        Err(_) => find_id(
+            "Task",
+            query,
+            tasks.iter().map(|task| (task.id, task.name.as_str())),
+        )?,
+    };
+    return Ok(
+        task_id.and_then(|task_id| tasks.into_iter().find(|task| task.id == task_id).cloned())
+    );
+}
+
+fn project_names(projects: &[Project]) -> impl Iterator<Item = (u64, &str)> {
+    return projects
+        .iter()
+        .map(|project| (project.id, project.name.as_str()));
 }
 
-fn format_time(datetime: &DateTime<Local>) -> String {
-    return datetime.format("%H:%M").to_string();
+fn workspace_names(workspaces: &[Workspace]) -> impl Iterator<Item = (u64, &str)> {
+    return workspaces
+        .iter()
+        .map(|workspace| (workspace.id, workspace.name.as_str()));
 }
 
-fn default_if_empty<'a>(text: &'a String, default: &'a String) -> &'a String {
-    if text.is_empty() {
-        return default;
+pub fn is_offline(error: &anyhow::Error) -> bool {
+    return error.chain().any(|cause| {
+        matches!(cause.downcast_ref::<ApiError>(), Some(ApiError::Network(error)) if error.is_connect())
+    });
+}
+
+fn is_retryable(error: &anyhow::Error) -> bool {
+    return is_offline(error)
+        || error.chain().any(|cause| {
+            matches!(
+                cause.downcast_ref::<ApiError>(),
+                Some(
+                    ApiError::Unauthorized { .. }
+                        | ApiError::RateLimited { .. }
+                        | ApiError::Server { .. }
+                )
+            )
+        });
+}
+
+fn is_timeout(error: &anyhow::Error) -> bool {
+    return error.chain().any(|cause| {
+        matches!(cause.downcast_ref::<ApiError>(), Some(ApiError::Network(error)) if error.is_timeout())
+    });
+}
+
+fn unconfirmed_creation(error: anyhow::Error) -> anyhow::Error {
+    return error.context(
+        "Toggl did not respond in time, the time entry may have been created anyway. Check `recent` before trying again",
+    );
+}
+
+fn none_if_empty(tags: Vec<String>) -> Option<Vec<String>> {
+    if tags.is_empty() {
+        return None;
     }
-    return text;
+    return Some(tags);
 }
@@ -0,0 +1,61 @@
+use anyhow::{Context, Ok, Result};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::{api_client::TimeEntryDto, dirs::find_global_config_dir};
+
+const UNDO_WINDOW_MINUTES: i64 = 30;
+
+#[derive(Deserialize, Serialize)]
+struct DeletedEntry {
+    deleted_at: i64,
+    time_entry: TimeEntryDto,
+}
+
+pub fn save_deleted_entry(time_entry: TimeEntryDto) -> Result<()> {
+    let deleted_entry = DeletedEntry {
+        deleted_at: Utc::now().timestamp(),
+        time_entry,
+    };
+    let value =
+        serde_json::to_string(&deleted_entry).context("Couldn't serialize deleted entry")?;
+    fs::write(deleted_entry_path()?, value).context("Could not save deleted entry")?;
+    return Ok(());
+}
+
+pub fn load_deleted_entry() -> Result<Option<TimeEntryDto>> {
+    let path = deleted_entry_path()?;
+    let exists = path
+        .try_exists()
+        .context("Couldn't read deleted entry file")?;
+    if !exists {
+        return Ok(None);
+    }
+
+    let value = fs::read_to_string(&path).context("Couldn't read deleted entry file")?;
+    let deleted_entry: DeletedEntry =
+        serde_json::from_str(&value).context("Couldn't parse deleted entry file")?;
+
+    let window = Duration::minutes(UNDO_WINDOW_MINUTES).num_seconds();
+    if Utc::now().timestamp() - deleted_entry.deleted_at > window {
+        clear_deleted_entry()?;
+        return Ok(None);
+    }
+    return Ok(Some(deleted_entry.time_entry));
+}
+
+pub fn clear_deleted_entry() -> Result<()> {
+    let path = deleted_entry_path()?;
+    if path
+        .try_exists()
+        .context("Couldn't read deleted entry file")?
+    {
+        fs::remove_file(&path).context("Couldn't remove deleted entry file")?;
+    }
+    return Ok(());
+}
+
+fn deleted_entry_path() -> Result<PathBuf> {
+    return Ok(find_global_config_dir()?.join("deleted_entry.json"));
+}
@@ -0,0 +1,831 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::{fs, process::Command, time::Duration};
+
+use chrono::{DateTime, Utc};
+use common::{expect_success, minutes_ago, stderr, unreachable_url, Cli, FakeToggl, EMAIL};
+use serde_json::Value;
+
+fn start_of(entry: &Value) -> DateTime<Utc> {
+    return entry["start"].as_str().unwrap().parse().unwrap();
+}
+
+fn offline_cli(server: &FakeToggl) -> Cli {
+    let mut cli = Cli::logged_in(server);
+    cli.ok(&["set", "--global", "--max-retries", "0"]);
+    cli.url = unreachable_url();
+    return cli;
+}
+
+#[test]
+fn completions_are_generated_without_a_token() {
+    let server = FakeToggl::start();
+    let cli = Cli::new(&server);
+
+    let stdout = cli.ok(&["completions", "bash"]);
+
+    assert!(stdout.contains("toggl-cli"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn login_stores_the_token_and_caches_entities() {
+    let server = FakeToggl::start();
+    let cli = Cli::new(&server);
+    cli.ok(&["set", "--global", "--token-backend", "file"]);
+
+    let stdout = cli.ok(&["login", "secret-token"]);
+
+    assert!(stdout.contains("Logged in as Jane Doe <jane@example.com>"));
+    assert!(cli.config_dir().join("api_token").exists());
+    let config = fs::read_to_string(cli.config_dir().join("config.toml")).unwrap();
+    assert!(config.contains("workspace_id = 1"));
+    assert!(config.contains("Backend"));
+    assert!(config.contains("Acme Corp"));
+    assert!(config.contains("Reviews"));
+    assert_eq!(cli.ok(&["default-workspace-id"]), "Workspace id 1\n");
+}
+
+#[test]
+fn login_rejects_an_invalid_token() {
+    let server = FakeToggl::start();
+    let cli = Cli::new(&server);
+    cli.ok(&["set", "--global", "--token-backend", "file"]);
+
+    let (code, stderr) = cli.fail(&["login", "wrong-token"]);
+
+    assert_eq!(code, 3);
+    assert!(stderr.contains("Could not validate the API token"));
+    assert!(!cli.config_dir().join("api_token").exists());
+}
+
+#[test]
+fn login_with_email_fetches_the_token() {
+    let server = FakeToggl::start();
+    let cli = Cli::new(&server);
+    cli.ok(&["set", "--global", "--token-backend", "file"]);
+
+    let output = cli.run_with_input(&["login", "--email", EMAIL], "hunter2\n");
+
+    let stdout = expect_success(&["login"], output);
+    assert!(stdout.contains("Logged in as Jane Doe"));
+    assert_eq!(
+        fs::read_to_string(cli.config_dir().join("api_token"))
+            .unwrap()
+            .trim(),
+        "secret-token"
+    );
+    let (code, _) = cli.fail(&["login", "--email", "nobody@example.com"]);
+    assert_eq!(code, 1);
+}
+
+#[test]
+fn logout_deletes_the_token_and_cached_entities() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    let stdout = cli.ok(&["logout"]);
+
+    assert!(stdout.contains("Deleted API token"));
+    assert!(!cli.config_dir().join("api_token").exists());
+    let config = fs::read_to_string(cli.config_dir().join("config.toml")).unwrap();
+    assert!(!config.contains("Backend"));
+    let (_, stderr) = cli.fail(&["status"]);
+    assert!(stderr.contains("login"));
+}
+
+#[test]
+fn token_command_runs_only_for_commands_using_toggl() {
+    let server = FakeToggl::start();
+    let cli = Cli::new(&server);
+    let log = cli.work_dir.join("token-command.log");
+    cli.ok(&[
+        "set",
+        "--global",
+        "--token-backend",
+        "command",
+        "--token-command",
+        "echo ran >> token-command.log; echo secret-token",
+    ]);
+
+    cli.ok(&["completions", "bash"]);
+    cli.ok(&["profile", "list"]);
+    cli.ok(&["set", "--global", "--week-start", "monday"]);
+    cli.fail(&["logout"]);
+
+    assert!(!log.exists());
+    cli.ok(&["status"]);
+    assert_eq!(fs::read_to_string(&log).unwrap(), "ran\n");
+}
+
+#[test]
+fn start_status_and_stop() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    let stdout = cli.ok(&[
+        "start",
+        "Code review",
+        "-p",
+        "back",
+        "--tag",
+        "review",
+        "-s",
+        "-20m",
+    ]);
+
+    assert!(stdout.contains("Time entry started"));
+    let entry = server.entry("Code review");
+    assert_eq!(entry["project_id"], 1);
+    assert_eq!(entry["tags"], serde_json::json!(["review"]));
+    assert_eq!(entry["billable"], true);
+    let minutes = (Utc::now() - start_of(&entry)).num_minutes();
+    assert!(
+        (19..=20).contains(&minutes),
+        "started {} minutes ago",
+        minutes
+    );
+
+    let status = cli.json(&["status"]);
+    assert_eq!(status["description"], "Code review");
+    assert_eq!(status["project_name"], "Backend");
+    assert_eq!(status["running"], true);
+
+    assert!(cli.ok(&["stop"]).contains("Stopped time entry"));
+    assert!(server.running().is_none());
+    assert!(server.entry("Code review")["duration"].as_i64().unwrap() >= 1200);
+    assert_eq!(cli.ok(&["stop"]), "There are no active time entries\n");
+    assert_eq!(cli.ok(&["status"]), "There are no active time entries\n");
+}
+
+#[test]
+fn start_with_a_task_uses_its_project() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    let started = cli.json(&["start", "Pairing", "--task", "rev", "--non-billable"]);
+
+    assert_eq!(started["task_name"], "Reviews");
+    let entry = server.entry("Pairing");
+    assert_eq!(entry["task_id"], 20);
+    assert_eq!(entry["project_id"], 1);
+    assert_eq!(entry["billable"], false);
+}
+
+#[test]
+fn add_creates_a_completed_entry() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    let stdout = cli.ok(&["add", "yesterday 9:00-10:30", "Planning", "-p", "Frontend"]);
+
+    assert!(stdout.contains("Time entry added"));
+    let entry = server.entry("Planning");
+    assert_eq!(entry["duration"], 5400);
+    assert_eq!(entry["project_id"], 2);
+    assert_eq!(entry["billable"], false);
+    assert!(entry["start"].as_str().unwrap().ends_with("T09:00:00Z"));
+}
+
+#[test]
+fn add_rejects_an_empty_range() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    server.clear_requests();
+
+    let (code, stderr) = cli.fail(&["add", "9:00-9:00", "Nothing"]);
+
+    assert_eq!(code, 1);
+    assert!(stderr.contains("empty"));
+    assert_eq!(server.count("POST", "workspaces/1/time_entries"), 0);
+}
+
+#[test]
+fn edit_updates_an_entry() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    let id = server.add_entry("Typo", &minutes_ago(60), Some(&minutes_ago(30)));
+
+    let stdout = cli.ok(&[
+        "edit",
+        "--id",
+        &id.to_string(),
+        "-d",
+        "Fixed",
+        "-p",
+        "Frontend",
+        "--tag",
+        "docs",
+    ]);
+
+    assert!(stdout.contains("Time entry updated"));
+    let entry = server.entry("Fixed");
+    assert_eq!(entry["project_id"], 2);
+    assert_eq!(entry["tags"], serde_json::json!(["docs"]));
+}
+
+#[test]
+fn edit_of_an_unknown_entry_exits_with_not_found() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    let (code, stderr) = cli.fail(&["edit", "--id", "999", "-d", "Nothing"]);
+
+    assert_eq!(code, 4);
+    assert!(stderr.contains("not found"));
+}
+
+#[test]
+fn delete_and_undo() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    let id = server.add_entry("Meeting", &minutes_ago(60), Some(&minutes_ago(30)));
+
+    let stdout = cli.ok(&["delete", "--id", &id.to_string(), "--yes"]);
+
+    assert!(stdout.contains("Time entry deleted"));
+    assert!(server.entries().is_empty());
+    assert!(cli.ok(&["undo"]).contains("Time entry restored"));
+    let restored = server.entry("Meeting");
+    assert_eq!(restored["duration"], 1800);
+    assert_eq!(cli.ok(&["undo"]), "There is nothing to undo\n");
+}
+
+#[test]
+fn delete_asks_for_confirmation() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    let id = server.add_entry("Meeting", &minutes_ago(60), Some(&minutes_ago(30)));
+
+    let output = cli.run_with_input(&["delete", "--id", &id.to_string()], "n\n");
+
+    let stdout = expect_success(&["delete"], output);
+    assert!(stdout.contains("Delete this time entry? [y/N]"));
+    assert!(stdout.contains("Time entry was not deleted"));
+    assert_eq!(server.entries().len(), 1);
+    assert_eq!(
+        cli.ok(&["delete", "--yes"]),
+        "There are no time entries to delete\n"
+    );
+    assert!(cli
+        .ok(&["delete", "--last", "--yes"])
+        .contains("Time entry deleted"));
+    assert!(server.entries().is_empty());
+}
+
+#[test]
+fn undo_keeps_the_entry_when_restoring_fails() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    let id = server.add_entry("Meeting", &minutes_ago(60), Some(&minutes_ago(30)));
+    cli.ok(&["delete", "--id", &id.to_string(), "--yes"]);
+    server.respond("POST", "workspaces/1/time_entries", 500, "Oops");
+
+    let (code, _) = cli.fail(&["undo"]);
+
+    assert_eq!(code, 6);
+    assert!(server.entries().is_empty());
+    assert!(cli.ok(&["undo"]).contains("Time entry restored"));
+    assert_eq!(server.entries().len(), 1);
+}
+
+#[test]
+fn recent_lists_entries() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    let first = server.add_entry("First", &minutes_ago(90), Some(&minutes_ago(60)));
+    let second = server.add_entry("Second", &minutes_ago(50), None);
+
+    let stdout = cli.ok(&["recent", "--ids"]);
+
+    assert!(stdout.contains(&format!("[{}]", first)));
+    assert!(stdout.contains(&format!("[{}]", second)));
+    let entries = cli.json(&["--refresh", "recent"]);
+    assert_eq!(entries.as_array().unwrap().len(), 2);
+    assert_eq!(entries[0]["description"], "Second");
+    assert_eq!(entries[0]["running"], true);
+    assert!(cli.ok(&[]).contains("First"));
+}
+
+#[test]
+fn summary_groups_entries() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    server.add_entry("Deep work", &minutes_ago(90), Some(&minutes_ago(30)));
+    server.add_entry("Email", &minutes_ago(25), Some(&minutes_ago(15)));
+
+    let summary = cli.json(&[
+        "summary",
+        "--from",
+        "yesterday",
+        "--group-by",
+        "description",
+    ]);
+
+    assert_eq!(summary["total"], 4200);
+    assert_eq!(summary["billable"], 0);
+    let groups = summary["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0]["name"], "Deep work");
+    assert_eq!(groups[0]["duration"], 3600);
+    assert!(cli.ok(&["summary"]).contains("Email"));
+}
+
+#[test]
+fn export_writes_csv() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    server.add_entry("Deep work", &minutes_ago(90), Some(&minutes_ago(30)));
+
+    let stdout = cli.ok(&["export", "--from", "yesterday"]);
+
+    assert!(stdout.starts_with("id,date,start,stop,duration,project,description,tags\n"));
+    assert!(stdout.contains("Deep work"));
+    cli.ok(&["export", "--from", "yesterday", "--file", "out.csv"]);
+    let file = fs::read_to_string(cli.work_dir.join("out.csv")).unwrap();
+    assert_eq!(file, stdout);
+}
+
+#[test]
+fn offline_changes_are_queued_and_synced() {
+    let server = FakeToggl::start();
+    let mut cli = offline_cli(&server);
+    server.add_entry("Earlier", &minutes_ago(60), None);
+
+    let stdout = cli.ok(&["start", "Offline work", "-s", "-30m"]);
+    assert!(stdout.contains("The change was queued, run sync to send it"));
+    assert!(cli.ok(&["stop"]).contains("The change was queued"));
+    let (code, _) = cli.fail(&["sync"]);
+    assert_eq!(code, 8);
+
+    cli.url = server.url.clone();
+    let report = cli.json(&["sync"]);
+
+    assert_eq!(report["synced"].as_array().unwrap().len(), 2);
+    assert_eq!(report["conflicts"], serde_json::json!([]));
+    assert!(server.running().is_none());
+    let earlier = server.entry("Earlier");
+    let offline = server.entry("Offline work");
+    assert_eq!(earlier["stop"], offline["start"]);
+    let minutes = offline["duration"].as_i64().unwrap() / 60;
+    assert!((29..=30).contains(&minutes), "ran {} minutes", minutes);
+    assert_eq!(cli.ok(&["sync"]), "There are no queued changes\n");
+}
+
+#[test]
+fn queued_stop_does_not_stop_an_entry_started_elsewhere() {
+    let server = FakeToggl::start();
+    let mut cli = offline_cli(&server);
+    cli.ok(&["start", "Offline work", "-s", "-30m"]);
+    cli.ok(&["stop"]);
+    server.add_entry("Started elsewhere", &minutes_ago(10), None);
+
+    cli.url = server.url.clone();
+    let report = cli.json(&["sync"]);
+
+    assert_eq!(report["synced"], serde_json::json!([]));
+    let conflicts = report["conflicts"].as_array().unwrap();
+    assert_eq!(conflicts.len(), 2);
+    assert!(conflicts[0].as_str().unwrap().starts_with("Not started"));
+    assert!(conflicts[1].as_str().unwrap().starts_with("Not stopped"));
+    assert_eq!(
+        server.running().unwrap()["description"],
+        "Started elsewhere"
+    );
+}
+
+#[test]
+fn queued_changes_are_replayed_before_other_commands() {
+    let server = FakeToggl::start();
+    let mut cli = offline_cli(&server);
+    cli.ok(&["add", "yesterday 9:00-10:00", "Queued"]);
+
+    cli.url = server.url.clone();
+    let output = cli.run(&["status"]);
+
+    let status_error = stderr(&output);
+    assert_eq!(
+        expect_success(&["status"], output),
+        "There are no active time entries\n"
+    );
+    assert!(status_error.contains("Synced queued change"));
+    assert_eq!(server.entry("Queued")["duration"], 3600);
+    assert_eq!(cli.ok(&["sync"]), "There are no queued changes\n");
+}
+
+#[test]
+fn rejected_queued_changes_are_dropped() {
+    let server = FakeToggl::start();
+    let mut cli = offline_cli(&server);
+    cli.ok(&["add", "yesterday 9:00-10:00", "Queued"]);
+    server.respond("POST", "workspaces/1/time_entries", 400, "Invalid project");
+
+    cli.url = server.url.clone();
+    let output = cli.run(&["status"]);
+
+    let status_error = stderr(&output);
+    expect_success(&["status"], output);
+    assert!(status_error.contains("Conflict: Dropped queued entry \"Queued\""));
+    assert!(status_error.contains("Invalid project"));
+    assert!(server.entries().is_empty());
+    assert_eq!(cli.ok(&["sync"]), "There are no queued changes\n");
+}
+
+#[test]
+fn queued_changes_are_kept_when_toggl_fails() {
+    let server = FakeToggl::start();
+    let mut cli = offline_cli(&server);
+    cli.ok(&["add", "yesterday 9:00-10:00", "Queued"]);
+    server.respond("POST", "workspaces/1/time_entries", 503, "Maintenance");
+
+    cli.url = server.url.clone();
+    let (code, stderr) = cli.fail(&["sync"]);
+
+    assert_eq!(code, 6);
+    assert!(stderr.contains("Could not sync queued time entry changes"));
+    let report = cli.json(&["sync"]);
+    assert_eq!(report["synced"][0]["description"], "Queued");
+}
+
+#[test]
+fn restart_and_switch() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    server.add_entry("First", &minutes_ago(120), Some(&minutes_ago(90)));
+    server.add_entry("Second", &minutes_ago(80), Some(&minutes_ago(60)));
+
+    assert!(cli.ok(&["restart"]).contains("Time entry started"));
+    assert_eq!(server.running().unwrap()["description"], "Second");
+
+    cli.ok(&["start", "Third"]);
+    cli.ok(&["switch"]);
+
+    let running = server.running().unwrap();
+    assert_eq!(running["description"], "Second");
+    assert!(server.entry("Third")["stop"].is_string());
+}
+
+#[test]
+fn restart_without_entries() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    assert_eq!(cli.ok(&["restart"]), "There are no recent entries\n");
+    assert_eq!(cli.ok(&["switch"]), "There are no recent entries\n");
+}
+
+#[test]
+fn workspaces_are_cached() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    server.clear_requests();
+
+    assert_eq!(cli.ok(&["workspaces"]), "[1] Main\n[2] Side\n");
+    cli.ok(&["workspaces"]);
+    assert_eq!(server.count("GET", "me/workspaces"), 1);
+
+    cli.ok(&["--refresh", "workspaces"]);
+    assert_eq!(server.count("GET", "me/workspaces"), 2);
+}
+
+#[test]
+fn workspace_can_be_selected_by_name() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    cli.ok(&[
+        "--workspace",
+        "side",
+        "add",
+        "yesterday 9:00-10:00",
+        "Elsewhere",
+    ]);
+
+    assert_eq!(server.entry("Elsewhere")["workspace_id"], 2);
+    let (code, stderr) = cli.fail(&["--workspace", "Missing", "status"]);
+    assert_eq!(code, 1);
+    assert!(stderr.contains("Workspace \"Missing\" not found"));
+}
+
+#[test]
+fn clients_tasks_and_projects() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    assert_eq!(cli.ok(&["clients"]), "[10] Acme Corp\n");
+    assert_eq!(
+        cli.ok(&["tasks", "--project", "Backend"]),
+        "[20] Reviews\tproject 1\n"
+    );
+    assert_eq!(cli.json(&["tasks"]).as_array().unwrap().len(), 2);
+    assert_eq!(cli.ok(&["projects"]), "[1] Backend\n[2] Frontend\n");
+}
+
+#[test]
+fn project_commands() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    let stdout = cli.ok(&[
+        "project",
+        "create",
+        "Mobile",
+        "--client-id",
+        "10",
+        "--color",
+        "FF8800",
+        "--billable",
+        "--estimate",
+        "40",
+    ]);
+
+    assert!(stdout.starts_with("Project created: ["));
+    let created = server.projects().pop().unwrap();
+    assert_eq!(created["name"], "Mobile");
+    assert_eq!(created["client_id"], 10);
+    assert_eq!(created["color"], "#ff8800");
+    assert_eq!(created["billable"], true);
+    assert_eq!(created["estimated_hours"], 40);
+
+    assert!(cli
+        .ok(&["project", "rename", "Mobile", "Apps"])
+        .contains("Project renamed"));
+    assert!(cli
+        .ok(&["project", "color", "Apps", "#06AAF5"])
+        .contains("Project colour changed"));
+    assert!(cli
+        .ok(&["project", "archive", "Apps"])
+        .contains("Project archived"));
+    let updated = server.projects().pop().unwrap();
+    assert_eq!(updated["name"], "Apps");
+    assert_eq!(updated["color"], "#06aaf5");
+    assert_eq!(updated["active"], false);
+
+    let shown = cli.json(&["project", "show", "Backend"]);
+    assert_eq!(shown["client_id"], 10);
+    assert_eq!(shown["actual_hours"], 3);
+    let (code, _) = cli.fail(&["project", "color", "Backend", "blue"]);
+    assert_eq!(code, 2);
+}
+
+#[test]
+fn project_commands_use_the_workspace_of_the_project() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    let id = server.add_project("Side project", 2);
+
+    let shown = cli.json(&["project", "show", "Side"]);
+
+    assert_eq!(shown["id"], id);
+    assert_eq!(shown["workspace_id"], 2);
+    cli.ok(&["project", "rename", &id.to_string(), "Renamed"]);
+    assert_eq!(server.projects().pop().unwrap()["name"], "Renamed");
+    assert_eq!(
+        server.count("PUT", &format!("workspaces/2/projects/{}", id)),
+        1
+    );
+}
+
+#[test]
+fn tag_commands() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    assert_eq!(cli.ok(&["tags"]), "[1] review\n");
+    assert!(cli
+        .ok(&["tags", "create", "urgent"])
+        .contains("Tag created: ["));
+    assert!(cli
+        .ok(&["tags", "rename", "urgent", "asap"])
+        .contains("Tag renamed"));
+
+    let names: Vec<Value> = server
+        .tags()
+        .into_iter()
+        .map(|tag| tag["name"].clone())
+        .collect();
+    assert_eq!(names, vec!["review", "asap"]);
+    assert!(cli.ok(&["tags", "list"]).contains("asap"));
+    let config = fs::read_to_string(cli.config_dir().join("config.toml")).unwrap();
+    assert!(!config.contains("asap"));
+    let (code, stderr) = cli.fail(&["tags", "rename", "missing", "other"]);
+    assert_eq!(code, 1);
+    assert!(stderr.contains("Tag \"missing\" not found"));
+}
+
+#[test]
+fn set_writes_global_and_local_config() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    cli.ok(&[
+        "set",
+        "--global",
+        "--week-start",
+        "Sunday",
+        "--cache-ttl",
+        "0",
+    ]);
+    cli.ok(&["set", "--project", "Front"]);
+
+    let global = fs::read_to_string(cli.config_dir().join("config.toml")).unwrap();
+    assert!(global.contains("week_start = \"sunday\""));
+    assert!(global.contains("cache_ttl = 0"));
+    let local = fs::read_to_string(cli.work_dir.join(".toggl")).unwrap();
+    assert!(local.contains("project_id = 2"));
+    cli.ok(&["start", "Default project"]);
+    assert_eq!(server.entry("Default project")["project_id"], 2);
+
+    let (code, _) = cli.fail(&["set", "--api-url", "http://localhost"]);
+    assert_eq!(code, 2);
+    let (code, _) = cli.fail(&["set", "--week-start", "someday"]);
+    assert_eq!(code, 2);
+}
+
+#[test]
+fn profiles_have_their_own_token() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+
+    assert!(cli
+        .ok(&["profile", "add", "work"])
+        .contains("Profile work added"));
+    assert_eq!(cli.ok(&["profile"]), "* default\n  work\n");
+    let profiles = cli.json(&["profile", "list"]);
+    assert_eq!(profiles[1]["name"], "work");
+    assert_eq!(profiles[1]["active"], false);
+
+    assert_eq!(
+        cli.ok(&["profile", "use", "work"]),
+        "Using profile work by default\n"
+    );
+    let (_, stderr) = cli.fail(&["status"]);
+    assert!(stderr.contains("login"));
+    cli.ok(&["--profile", "default", "status"]);
+
+    cli.ok(&["profile", "use", "default"]);
+    assert_eq!(
+        cli.ok(&["profile", "remove", "work"]),
+        "Profile work removed\n"
+    );
+    assert_eq!(cli.ok(&["profile"]), "* default\n");
+    let (code, _) = cli.fail(&["--profile", "work", "status"]);
+    assert_eq!(code, 1);
+}
+
+#[test]
+fn removing_a_profile_deletes_its_token_file() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    cli.ok(&["profile", "add", "work"]);
+    cli.ok(&[
+        "--profile",
+        "work",
+        "set",
+        "--global",
+        "--token-backend",
+        "file",
+    ]);
+    cli.ok(&["--profile", "work", "login", "secret-token"]);
+    let profile_dir = cli.config_dir().join("profiles").join("work");
+    assert!(profile_dir.join("api_token").exists());
+
+    cli.ok(&["profile", "remove", "work"]);
+
+    assert!(!profile_dir.exists());
+    cli.ok(&["status"]);
+}
+
+#[test]
+fn rate_limited_requests_are_retried() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    server.rate_limit("GET", "me/time_entries/current", "0");
+
+    cli.ok(&["status"]);
+
+    assert_eq!(server.count("GET", "me/time_entries/current"), 2);
+}
+
+#[test]
+fn server_errors_are_retried_only_for_idempotent_requests() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    server.respond("GET", "me/time_entries/current", 502, "Bad gateway");
+
+    cli.ok(&["status"]);
+
+    assert_eq!(server.count("GET", "me/time_entries/current"), 2);
+
+    server.respond("POST", "workspaces/1/time_entries", 500, "Oops");
+    let (code, _) = cli.fail(&["start", "Not retried"]);
+    assert_eq!(code, 6);
+    assert_eq!(server.count("POST", "workspaces/1/time_entries"), 1);
+    assert!(server.entries().is_empty());
+    assert_eq!(cli.ok(&["sync"]), "There are no queued changes\n");
+}
+
+#[test]
+fn retries_give_up_after_max_retries() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    cli.ok(&["set", "--global", "--max-retries", "1"]);
+    server.respond_times("GET", "me/time_entries/current", 503, "Maintenance", 2);
+
+    let (code, stderr) = cli.fail(&["status"]);
+
+    assert_eq!(code, 6);
+    assert!(stderr.contains("Toggl server error (503 Service Unavailable): Maintenance"));
+    assert_eq!(server.count("GET", "me/time_entries/current"), 2);
+}
+
+#[test]
+fn api_errors_have_distinct_exit_codes() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    cli.ok(&["set", "--global", "--max-retries", "0"]);
+
+    for (status, code) in [(401, 3), (404, 4), (429, 5), (500, 6), (418, 7)] {
+        server.respond("GET", "me/time_entries/current", status, "Nope");
+        let (exit_code, stderr) = cli.fail(&["status"]);
+        assert_eq!(exit_code, code, "status {}: {}", status, stderr);
+    }
+
+    let mut cli = cli;
+    cli.url = unreachable_url();
+    let (code, stderr) = cli.fail(&["status"]);
+    assert_eq!(code, 8);
+    assert!(stderr.contains("Could not reach Toggl"));
+
+    cli.url = "http://exa mple.com/".to_string();
+    let (code, _) = cli.fail(&["start", "Invalid URL"]);
+    assert_eq!(code, 1);
+    cli.url = server.url.clone();
+    assert_eq!(cli.ok(&["sync"]), "There are no queued changes\n");
+}
+
+#[test]
+fn timed_out_start_is_not_queued() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    cli.ok(&["set", "--global", "--timeout", "1"]);
+    server.delay("POST", "workspaces/1/time_entries", Duration::from_secs(2));
+
+    let (code, stderr) = cli.fail(&["start", "Slow"]);
+
+    assert_eq!(code, 8);
+    assert!(stderr.contains("Check `recent` before trying again"));
+    assert_eq!(server.count("POST", "workspaces/1/time_entries"), 1);
+    assert_eq!(cli.ok(&["sync"]), "There are no queued changes\n");
+}
+
+#[test]
+fn start_derives_details_from_the_git_branch() {
+    let server = FakeToggl::start();
+    let cli = Cli::logged_in(&server);
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(&cli.work_dir)
+            .env("HOME", cli.home())
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&[
+        "init",
+        "--quiet",
+        "--initial-branch",
+        "feature/ABC-42-login",
+    ]);
+    fs::write(
+        cli.work_dir.join(".toggl"),
+        "[[branches]]\npattern = \"feature/*\"\nproject = \"Frontend\"\ntags = [\"feature\"]\n",
+    )
+    .unwrap();
+
+    cli.ok(&["start"]);
+
+    let entry = server.entry("ABC-42");
+    assert_eq!(entry["project_id"], 2);
+    assert_eq!(entry["tags"], serde_json::json!(["feature"]));
+
+    git(&["checkout", "--quiet", "-b", "cleanup"]);
+    cli.ok(&["start"]);
+    assert_eq!(server.running().unwrap()["description"], "cleanup");
+
+    cli.ok(&["set", "--description-template", "{ticket}: {name}"]);
+    git(&["checkout", "--quiet", "-b", "fix/XYZ-7-crash"]);
+    cli.ok(&["start"]);
+    assert_eq!(
+        server.running().unwrap()["description"],
+        "XYZ-7: XYZ-7-crash"
+    );
+
+    git(&["checkout", "--quiet", "-b", "main"]);
+    cli.ok(&["start"]);
+    assert!(server.running().unwrap()["description"].is_null());
+}
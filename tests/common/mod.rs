@@ -0,0 +1,676 @@
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    io::Write,
+    net::TcpListener,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, SecondsFormat, Timelike, Utc};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tiny_http::{Header, Method, Response, Server};
+
+pub const API_TOKEN: &str = "secret-token";
+pub const EMAIL: &str = "jane@example.com";
+pub const PASSWORD: &str = "hunter2";
+pub const WORKSPACE_ID: u64 = 1;
+
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Value,
+}
+
+struct Rule {
+    method: &'static str,
+    path: String,
+    status: u16,
+    body: String,
+    headers: Vec<(&'static str, String)>,
+    delay: Duration,
+    remaining: usize,
+}
+
+struct State {
+    entries: Vec<Value>,
+    projects: Vec<Value>,
+    tags: Vec<Value>,
+    next_id: u64,
+    rules: Vec<Rule>,
+    requests: Vec<RecordedRequest>,
+}
+
+pub struct FakeToggl {
+    pub url: String,
+    server: Arc<Server>,
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeToggl {
+    pub fn start() -> FakeToggl {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("Could not start fake Toggl"));
+        let port = server
+            .server_addr()
+            .to_ip()
+            .expect("Should listen on IP")
+            .port();
+        let state = Arc::new(Mutex::new(State {
+            entries: vec![],
+            projects: vec![
+                project_json(1, "Backend", Some(10), true),
+                project_json(2, "Frontend", None, false),
+            ],
+            tags: vec![json!({"id": 1, "name": "review"})],
+            next_id: 100,
+            rules: vec![],
+            requests: vec![],
+        }));
+
+        let accepting = server.clone();
+        let shared = state.clone();
+        thread::spawn(move || {
+            for request in accepting.incoming_requests() {
+                let state = shared.clone();
+                thread::spawn(move || handle(request, &state));
+            }
+        });
+
+        return FakeToggl {
+            url: format!("http://127.0.0.1:{}/api/v9/", port),
+            server,
+            state,
+        };
+    }
+
+    pub fn respond(&self, method: &'static str, path: &str, status: u16, body: &str) {
+        self.add_rule(method, path, status, body, vec![], Duration::ZERO, 1);
+    }
+
+    pub fn respond_times(
+        &self,
+        method: &'static str,
+        path: &str,
+        status: u16,
+        body: &str,
+        times: usize,
+    ) {
+        self.add_rule(method, path, status, body, vec![], Duration::ZERO, times);
+    }
+
+    pub fn rate_limit(&self, method: &'static str, path: &str, retry_after: &str) {
+        let headers = vec![("Retry-After", retry_after.to_string())];
+        self.add_rule(
+            method,
+            path,
+            429,
+            "Too many requests",
+            headers,
+            Duration::ZERO,
+            1,
+        );
+    }
+
+    pub fn delay(&self, method: &'static str, path: &str, delay: Duration) {
+        self.add_rule(method, path, 0, "", vec![], delay, 1);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_rule(
+        &self,
+        method: &'static str,
+        path: &str,
+        status: u16,
+        body: &str,
+        headers: Vec<(&'static str, String)>,
+        delay: Duration,
+        remaining: usize,
+    ) {
+        self.state.lock().unwrap().rules.push(Rule {
+            method,
+            path: path.to_string(),
+            status,
+            body: body.to_string(),
+            headers,
+            delay,
+            remaining,
+        });
+    }
+
+    pub fn add_entry(&self, description: &str, start: &str, stop: Option<&str>) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        let start_time: DateTime<Utc> = start.parse().unwrap();
+        let duration = match stop {
+            Some(stop) => (stop.parse::<DateTime<Utc>>().unwrap() - start_time).num_seconds(),
+            None => -start_time.timestamp(),
+        };
+        state.entries.push(json!({
+            "id": id,
+            "workspace_id": WORKSPACE_ID,
+            "description": description,
+            "project_id": null,
+            "task_id": null,
+            "tags": [],
+            "billable": false,
+            "start": start,
+            "stop": stop,
+            "duration": duration,
+        }));
+        return id;
+    }
+
+    pub fn add_project(&self, name: &str, workspace_id: u64) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        let mut project = project_json(id, name, None, false);
+        project["workspace_id"] = json!(workspace_id);
+        state.projects.push(project);
+        return id;
+    }
+
+    pub fn entries(&self) -> Vec<Value> {
+        return self.state.lock().unwrap().entries.clone();
+    }
+
+    pub fn entry(&self, description: &str) -> Value {
+        return self
+            .entries()
+            .into_iter()
+            .find(|entry| entry["description"] == description)
+            .unwrap_or_else(|| panic!("No time entry \"{}\"", description));
+    }
+
+    pub fn running(&self) -> Option<Value> {
+        return self
+            .entries()
+            .into_iter()
+            .find(|entry| entry["duration"].as_i64().unwrap() < 0);
+    }
+
+    pub fn projects(&self) -> Vec<Value> {
+        return self.state.lock().unwrap().projects.clone();
+    }
+
+    pub fn tags(&self) -> Vec<Value> {
+        return self.state.lock().unwrap().tags.clone();
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        return self.state.lock().unwrap().requests.clone();
+    }
+
+    pub fn count(&self, method: &str, path: &str) -> usize {
+        return self
+            .requests()
+            .iter()
+            .filter(|request| request.method == method && request.path == path)
+            .count();
+    }
+
+    pub fn clear_requests(&self) {
+        self.state.lock().unwrap().requests.clear();
+    }
+}
+
+impl Drop for FakeToggl {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+fn project_json(id: u64, name: &str, client_id: Option<u64>, billable: bool) -> Value {
+    return json!({
+        "id": id,
+        "workspace_id": WORKSPACE_ID,
+        "name": name,
+        "client_id": client_id,
+        "color": "#06aaf5",
+        "billable": billable,
+        "active": true,
+        "estimated_hours": null,
+        "actual_hours": 3,
+    });
+}
+
+fn handle(mut request: tiny_http::Request, state: &Mutex<State>) {
+    let method = request.method().to_string();
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let path = path.trim_start_matches("/api/v9/").to_string();
+    let query = query.to_string();
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body).unwrap();
+    let body: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+    let authorization = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .map(|header| header.value.to_string())
+        .unwrap_or_default();
+
+    let rule = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            method: method.clone(),
+            path: path.clone(),
+            body: body.clone(),
+        });
+        let index = state
+            .rules
+            .iter()
+            .position(|rule| rule.method == method && path.starts_with(&rule.path));
+        index.map(|index| {
+            let rule = &mut state.rules[index];
+            rule.remaining -= 1;
+            let taken = (
+                rule.status,
+                rule.body.clone(),
+                rule.headers.clone(),
+                rule.delay,
+            );
+            if rule.remaining == 0 {
+                state.rules.remove(index);
+            }
+            taken
+        })
+    };
+
+    if let Some((status, body, headers, delay)) = rule {
+        thread::sleep(delay);
+        if status != 0 {
+            let mut response = Response::from_string(body).with_status_code(status);
+            for (field, value) in headers {
+                response.add_header(Header::from_bytes(field, value).unwrap());
+            }
+            let _ = request.respond(response);
+            return;
+        }
+    }
+
+    let token_auth = basic_auth(API_TOKEN, "api_token");
+    let password_auth = basic_auth(EMAIL, PASSWORD);
+    if authorization != token_auth && !(path == "me" && authorization == password_auth) {
+        let response =
+            Response::from_string("Incorrect username and/or password").with_status_code(403);
+        let _ = request.respond(response);
+        return;
+    }
+
+    let (status, value) = route(
+        request.method(),
+        &path,
+        &query,
+        body,
+        &mut state.lock().unwrap(),
+    );
+    let response = Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+    let _ = request.respond(response);
+}
+
+fn route(method: &Method, path: &str, query: &str, body: Value, state: &mut State) -> (u16, Value) {
+    let segments: Vec<&str> = path.split('/').collect();
+    return match (method, segments.as_slice()) {
+        (Method::Get, ["me"]) => (
+            200,
+            json!({
+                "fullname": "Jane Doe",
+                "email": EMAIL,
+                "default_workspace_id": WORKSPACE_ID,
+                "api_token": API_TOKEN,
+            }),
+        ),
+        (Method::Get, ["me", "workspaces"]) => (
+            200,
+            json!([{"id": 1, "name": "Main"}, {"id": 2, "name": "Side"}]),
+        ),
+        (Method::Get, ["me", "clients"]) => (200, json!([{"id": 10, "name": "Acme Corp"}])),
+        (Method::Get, ["me", "tasks"]) => (
+            200,
+            json!([
+                {"id": 20, "name": "Reviews", "project_id": 1},
+                {"id": 21, "name": "Design", "project_id": 2},
+            ]),
+        ),
+        (Method::Get, ["me", "projects"]) => (200, Value::from(state.projects.clone())),
+        (Method::Get, ["me", "time_entries"]) => (200, Value::from(entries_between(state, query))),
+        (Method::Get, ["me", "time_entries", "current"]) => {
+            let running = state
+                .entries
+                .iter()
+                .find(|entry| entry["duration"].as_i64().unwrap() < 0)
+                .cloned();
+            (200, running.unwrap_or(Value::Null))
+        }
+        (Method::Get, ["me", "time_entries", id]) => match find_entry(state, id) {
+            Some(index) => (200, state.entries[index].clone()),
+            None => (404, json!("Time entry not found")),
+        },
+        (Method::Post, ["workspaces", _, "time_entries"]) => (200, create_entry(state, body)),
+        (Method::Put, ["workspaces", _, "time_entries", id]) => match find_entry(state, id) {
+            Some(index) => {
+                let entry = &mut state.entries[index];
+                for (key, value) in body.as_object().unwrap() {
+                    entry[key] = match (key.as_str(), value.as_str()) {
+                        ("start" | "stop", Some(time)) => {
+                            json!(format_time(truncate(time.parse().unwrap())))
+                        }
+                        _ => value.clone(),
+                    };
+                }
+                (200, entry.clone())
+            }
+            None => (404, json!("Time entry not found")),
+        },
+        (Method::Patch, ["workspaces", _, "time_entries", id, "stop"]) => {
+            match find_entry(state, id) {
+                Some(index) => {
+                    let entry = &mut state.entries[index];
+                    let start: DateTime<Utc> = entry["start"].as_str().unwrap().parse().unwrap();
+                    let now = truncate(Utc::now());
+                    entry["stop"] = json!(format_time(now));
+                    entry["duration"] = json!((now - start).num_seconds());
+                    (200, entry.clone())
+                }
+                None => (404, json!("Time entry not found")),
+            }
+        }
+        (Method::Delete, ["workspaces", _, "time_entries", id]) => match find_entry(state, id) {
+            Some(index) => {
+                state.entries.remove(index);
+                (200, Value::Null)
+            }
+            None => (404, json!("Time entry not found")),
+        },
+        (Method::Get, ["workspaces", workspace_id, "projects", id]) => {
+            match find_project(state, workspace_id, id) {
+                Some(index) => (200, state.projects[index].clone()),
+                None => (404, json!("Project not found")),
+            }
+        }
+        (Method::Post, ["workspaces", _, "projects"]) => {
+            let id = state.next_id;
+            state.next_id += 1;
+            let mut project = project_json(id, body["name"].as_str().unwrap(), None, false);
+            for key in [
+                "client_id",
+                "color",
+                "billable",
+                "estimated_hours",
+                "active",
+            ] {
+                if let Some(value) = body.get(key) {
+                    project[key] = value.clone();
+                }
+            }
+            project["actual_hours"] = Value::Null;
+            state.projects.push(project.clone());
+            (200, project)
+        }
+        (Method::Put, ["workspaces", workspace_id, "projects", id]) => {
+            match find_project(state, workspace_id, id) {
+                Some(index) => {
+                    let project = &mut state.projects[index];
+                    for (key, value) in body.as_object().unwrap() {
+                        project[key] = value.clone();
+                    }
+                    (200, project.clone())
+                }
+                None => (404, json!("Project not found")),
+            }
+        }
+        (Method::Get, ["workspaces", _, "tags"]) => (200, Value::from(state.tags.clone())),
+        (Method::Post, ["workspaces", _, "tags"]) => {
+            let id = state.next_id;
+            state.next_id += 1;
+            let tag = json!({"id": id, "name": body["name"]});
+            state.tags.push(tag.clone());
+            (200, tag)
+        }
+        (Method::Put, ["workspaces", _, "tags", id]) => {
+            let id: u64 = id.parse().unwrap();
+            match state.tags.iter_mut().find(|tag| tag["id"] == id) {
+                Some(tag) => {
+                    tag["name"] = body["name"].clone();
+                    (200, tag.clone())
+                }
+                None => (404, json!("Tag not found")),
+            }
+        }
+        _ => (404, json!(format!("Unknown endpoint {} {}", method, path))),
+    };
+}
+
+fn create_entry(state: &mut State, body: Value) -> Value {
+    let start = truncate(body["start"].as_str().unwrap().parse().unwrap());
+    let running = body["duration"].as_i64().unwrap() < 0;
+    if running {
+        for entry in state.entries.iter_mut() {
+            if entry["duration"].as_i64().unwrap() < 0 {
+                let entry_start: DateTime<Utc> = entry["start"].as_str().unwrap().parse().unwrap();
+                entry["stop"] = json!(format_time(start));
+                entry["duration"] = json!((start - entry_start).num_seconds());
+            }
+        }
+    }
+    let id = state.next_id;
+    state.next_id += 1;
+    let stop = body["stop"]
+        .as_str()
+        .map(|stop| format_time(truncate(stop.parse().unwrap())));
+    let entry = json!({
+        "id": id,
+        "workspace_id": body["workspace_id"],
+        "description": body["description"],
+        "project_id": body["project_id"],
+        "task_id": body.get("task_id").cloned().unwrap_or(Value::Null),
+        "tags": body.get("tags").cloned().unwrap_or(json!([])),
+        "billable": body["billable"],
+        "start": format_time(start),
+        "stop": stop,
+        "duration": if running { -start.timestamp() } else { body["duration"].as_i64().unwrap() },
+    });
+    state.entries.push(entry.clone());
+    return entry;
+}
+
+fn entries_between(state: &State, query: &str) -> Vec<Value> {
+    let parameter = |name: &str| {
+        return query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(&format!("{}=", name)))
+            .map(|value| value.to_string());
+    };
+    let mut entries: Vec<Value> = state
+        .entries
+        .iter()
+        .filter(|entry| {
+            let start = &entry["start"].as_str().unwrap()[..10];
+            let after = parameter("start_date").is_none_or(|from| start >= from.as_str());
+            let before = parameter("end_date").is_none_or(|to| start < to.as_str());
+            return after && before;
+        })
+        .cloned()
+        .collect();
+    entries.sort_by(|a, b| b["start"].as_str().cmp(&a["start"].as_str()));
+    return entries;
+}
+
+fn find_entry(state: &State, id: &str) -> Option<usize> {
+    let id: u64 = id.parse().ok()?;
+    return state.entries.iter().position(|entry| entry["id"] == id);
+}
+
+fn find_project(state: &State, workspace_id: &str, id: &str) -> Option<usize> {
+    let workspace_id: u64 = workspace_id.parse().ok()?;
+    let id: u64 = id.parse().ok()?;
+    return state
+        .projects
+        .iter()
+        .position(|project| project["id"] == id && project["workspace_id"] == workspace_id);
+}
+
+fn truncate(time: DateTime<Utc>) -> DateTime<Utc> {
+    return time.with_nanosecond(0).unwrap();
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    return time.to_rfc3339_opts(SecondsFormat::Secs, true);
+}
+
+fn basic_auth(user: &str, password: &str) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let input = format!("{}:{}", user, password).into_bytes();
+    let mut encoded = String::new();
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(value >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    return format!("Basic {}", encoded);
+}
+
+pub fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    return format!("http://127.0.0.1:{}/api/v9/", port);
+}
+
+pub struct Cli {
+    home: TempDir,
+    pub url: String,
+    pub work_dir: PathBuf,
+}
+
+impl Cli {
+    pub fn new(server: &FakeToggl) -> Cli {
+        let home = TempDir::new().unwrap();
+        let work_dir = home.path().join("work");
+        fs::create_dir_all(&work_dir).unwrap();
+        return Cli {
+            home,
+            url: server.url.clone(),
+            work_dir,
+        };
+    }
+
+    pub fn logged_in(server: &FakeToggl) -> Cli {
+        let cli = Cli::new(server);
+        cli.ok(&["set", "--global", "--token-backend", "file"]);
+        cli.ok(&["login", API_TOKEN]);
+        return cli;
+    }
+
+    pub fn home(&self) -> &Path {
+        return self.home.path();
+    }
+
+    pub fn config_dir(&self) -> PathBuf {
+        return self.home().join(".config").join("togglcli");
+    }
+
+    pub fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_toggl-cli"));
+        command
+            .args(args)
+            .current_dir(&self.work_dir)
+            .env_clear()
+            .env("PATH", std::env::var("PATH").unwrap_or_default())
+            .env("HOME", self.home())
+            .env("XDG_CONFIG_HOME", self.home().join(".config"))
+            .env("XDG_CACHE_HOME", self.home().join(".cache"))
+            .env("TOGGL_API_URL", &self.url)
+            .env("TZ", "UTC")
+            .env("RUST_BACKTRACE", "0")
+            .stdin(Stdio::null());
+        return command;
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        return self.command(args).output().unwrap();
+    }
+
+    pub fn run_with_input(&self, args: &[&str], input: &str) -> Output {
+        let mut child = self
+            .command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        return child.wait_with_output().unwrap();
+    }
+
+    pub fn ok(&self, args: &[&str]) -> String {
+        return expect_success(args, self.run(args));
+    }
+
+    pub fn json(&self, args: &[&str]) -> Value {
+        let mut args = args.to_vec();
+        args.extend(["--output", "json"]);
+        let stdout = self.ok(&args);
+        return serde_json::from_str(&stdout)
+            .unwrap_or_else(|error| panic!("Invalid JSON from {:?}: {}\n{}", args, error, stdout));
+    }
+
+    pub fn fail(&self, args: &[&str]) -> (i32, String) {
+        let output = self.run(args);
+        assert!(
+            !output.status.success(),
+            "Expected {:?} to fail, but it printed:\n{}",
+            args,
+            String::from_utf8_lossy(&output.stdout)
+        );
+        return (
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        );
+    }
+}
+
+pub fn expect_success(args: &[&str], output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "{:?} failed with {}:\n{}{}",
+        args,
+        output.status,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    return String::from_utf8_lossy(&output.stdout).to_string();
+}
+
+pub fn stderr(output: &Output) -> String {
+    return String::from_utf8_lossy(&output.stderr).to_string();
+}
+
+pub fn minutes_ago(minutes: i64) -> String {
+    return format_time(truncate(Utc::now() - chrono::Duration::minutes(minutes)));
+}